@@ -15,16 +15,135 @@ yes 12345678 | injectived tx wasm store artifacts/injective_agent_work.wasm \
 # Instantiate the contract
 # CODE_ID = 26518
 # INJ_ADDRESS = inj1z0ax5ypjskzhcsxhdz6sh5twvjdc6e4ta4f3rq
-INIT='{"token_symbol": "AWT", "token_contract_addr": "inj1wp6x43895dewtfugkv08tvu7ajmvthzvel5mwn"}'
+# an asset is {"cw20": {"addr": ...}} or {"native": {"denom": ...}} (bank or tokenfactory denom),
+# with a native asset stakes and bonds are paid by attaching --amount instead of an allowance
+# the sender becomes the admin of the config and the accepted assets, bond_asset must be one of them
+# protocol_fee_bps of the rewards released to agents is credited to the treasury (the sender when null)
+INIT='{"accepted_assets": [{"token_denom": "AWT", "asset": {"cw20": {"addr": "inj1wp6x43895dewtfugkv08tvu7ajmvthzvel5mwn"}}}], "bond_asset": {"cw20": {"addr": "inj1wp6x43895dewtfugkv08tvu7ajmvthzvel5mwn"}}, "treasury": null, "protocol_fee_bps": 100, "guardian": null, "arbiter_max_escrow": [{"asset": {"cw20": {"addr": "inj1wp6x43895dewtfugkv08tvu7ajmvthzvel5mwn"}}, "amount": "100"}]}'
 yes 12345678 | injectived tx wasm instantiate $CODE_ID "$INIT" \
 --label="Instantiate Injective Agent Work" \
 --from=$(echo $INJ_ADDRESS) \
 --chain-id="injective-888" \
 --yes --fees=1000000000000000inj \
 --gas=2000000 \
---no-admin \
+--admin=$(echo $INJ_ADDRESS) \
 --node=https://testnet.sentry.tm.injective.network:443
 
+# Migrate to a newly stored code id, the wasm admin set above signs it
+# downgrades are refused, baseline is only read when migrating the unversioned first deployment
+# NEW_CODE_ID = 26519
+MIGRATE='{"baseline": {"admin": "inj1z0ax5ypjskzhcsxhdz6sh5twvjdc6e4ta4f3rq", "treasury": null, "protocol_fee_bps": 100, "dispute_config": null}}'
+yes 12345678 | injectived tx wasm migrate inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc $NEW_CODE_ID "$MIGRATE" \
+--from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj \
+--gas=3000000 \
+--node=https://testnet.sentry.tm.injective.network:443
+
+# Fee manager updates the treasury and fee, the admin the arbiter limit, omitted fields are left unchanged
+UPDATE_CONFIG='{"update_config":{"treasury": "inj1z0ax5ypjskzhcsxhdz6sh5twvjdc6e4ta4f3rq", "protocol_fee_bps": 200, "arbiter_max_escrow": null}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$UPDATE_CONFIG" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Get config, the arbiter limit per asset and the protocol fees collected per asset
+GET_CONFIG='{"get_config": {}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GET_CONFIG" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+GET_ARBITER_MAX_ESCROW='{"get_arbiter_max_escrow": {}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GET_ARBITER_MAX_ESCROW" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+GET_PROTOCOL_FEES='{"get_protocol_fees": {}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GET_PROTOCOL_FEES" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Admin proposes a new admin, who has until the expiry (null for none) to accept with its own key.
+# renounce_admin {} leaves the contract without admin
+PROPOSE_NEW_ADMIN='{"propose_new_admin":{"addr": "inj1z0ax5ypjskzhcsxhdz6sh5twvjdc6e4ta4f3rq", "expiry": {"at_time": "1767225600000000000"}}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$PROPOSE_NEW_ADMIN" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+ACCEPT_ADMIN='{"accept_admin":{}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$ACCEPT_ADMIN" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Get the admin and the pending transfer
+GET_OWNERSHIP='{"get_ownership": {}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GET_OWNERSHIP" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Admin grants a role (admin, guardian, arbiter, fee_manager or juror_manager), revoke_role takes the same fields
+GRANT_ROLE='{"grant_role":{"role": "arbiter", "addr": "inj1z0ax5ypjskzhcsxhdz6sh5twvjdc6e4ta4f3rq"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GRANT_ROLE" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Check a role and list its members
+HAS_ROLE='{"has_role": {"role": "arbiter", "addr": "inj1z0ax5ypjskzhcsxhdz6sh5twvjdc6e4ta4f3rq"}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$HAS_ROLE" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+LIST_ROLE_MEMBERS='{"list_role_members": {"role": "arbiter", "start_after": null, "limit": 10}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$LIST_ROLE_MEMBERS" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Juror manager replaces the dispute config
+UPDATE_DISPUTE_CONFIG='{"update_dispute_config":{"dispute_config": {"commit_period": 86400, "reveal_period": 86400, "min_juror_bond": "100", "juror_unbonding_period": 604800, "vote_weighting": "linear", "max_vote_weight": null, "quorum": 1, "agent_slash_bps": 1000, "dispute_fee_bps": 500, "juror_slash_bps": 1000, "panel_size": 5, "max_jurors": 100, "randomness_beacon": null, "appeal_period": 86400, "max_appeals": 2, "appeal_bond": "100"}}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$UPDATE_DISPUTE_CONFIG" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Guardian pauses everything but withdrawals of credited balances,
+# unpause takes the same flags and lifts only those
+PAUSE='{"pause":{"flags": {"staking": true, "payouts": true, "voting": true, "withdrawals": false}}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$PAUSE" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+UNPAUSE='{"unpause":{"flags": {"staking": true, "payouts": true, "voting": true, "withdrawals": false}}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$UNPAUSE" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Get the paused operations
+GET_PAUSE_FLAGS='{"get_pause_flags": {}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GET_PAUSE_FLAGS" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Admin accepts INJ as payment asset, remove_asset takes {"asset": ...}
+ADD_ASSET='{"add_asset":{"token_denom": "INJ", "asset": {"native": {"denom": "inj"}}}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$ADD_ASSET" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Get accepted assets
+GET_ASSETS='{"get_assets": {}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GET_ASSETS" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
 # Query balance of specific address
 BALANCE_QUERY='{"balance": {"address": "inj1z0ax5ypjskzhcsxhdz6sh5twvjdc6e4ta4f3rq"}}'
 injectived query wasm contract-state smart inj1wp6x43895dewtfugkv08tvu7ajmvthzvel5mwn "$BALANCE_QUERY" \
@@ -37,6 +156,21 @@ injectived query wasm contract-state smart inj1wp6x43895dewtfugkv08tvu7ajmvthzve
 --node=https://testnet.sentry.tm.injective.network:443 \
 --output json 
 
+# User create job, the contract assigns the job id (first job is "1")
+# the owner funds the job and agents are paid in payment_asset
+CREATE_JOB='{"create_job":{"title": "Summarize Injective governance proposals", "spec_uri": "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi", "spec_hash": null, "budget": "100", "payment_asset": {"cw20": {"addr": "inj1wp6x43895dewtfugkv08tvu7ajmvthzvel5mwn"}}, "min_agent_stake": "50", "max_agents": 3}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$CREATE_JOB" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Get job
+GET_JOB='{"get_job": {"job_id": "1"}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GET_JOB" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
 # Increase allowance of user for contract
 INCREASE_ALLOWANCE='{"increase_allowance":{"spender": "inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc", "amount":"100", "expires": null}}'
 yes 12345678 | injectived tx wasm execute inj1wp6x43895dewtfugkv08tvu7ajmvthzvel5mwn "$INCREASE_ALLOWANCE" --from=$(echo $INJ_ADDRESS) \
@@ -53,6 +187,16 @@ yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002
 --node=https://testnet.sentry.tm.injective.network:443 \
 --output json
 
+# Or fund the job in one transaction by sending the tokens to the contract, no allowance needed
+# msg = base64 of the ReceiveMsg: {"fund_job":{"job_id":"1"}}, {"agent_stake":{...}} or {"bond_juror":{}}
+FUND_JOB_MSG=$(printf '%s' '{"fund_job":{"job_id":"1"}}' | base64)
+SEND='{"send":{"contract": "inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc", "amount":"100", "msg": "'$FUND_JOB_MSG'"}}'
+yes 12345678 | injectived tx wasm execute inj1wp6x43895dewtfugkv08tvu7ajmvthzvel5mwn "$SEND" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
 # Get user staked amount
 GET_USER_STAKE='{"get_user_stake": {"user_addr": "inj1z0ax5ypjskzhcsxhdz6sh5twvjdc6e4ta4f3rq", "job_id": "1"}}'
 injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GET_USER_STAKE" \
@@ -82,8 +226,78 @@ injectived query wasm contract-state smart inj1wp6x43895dewtfugkv08tvu7ajmvthzve
 --node=https://testnet.sentry.tm.injective.network:443 \
 --output json 
 
-# Agent stake to contract
-AGENT_STAKE='{"agent_stake":{"amount":"20", "job_id": "1", "cost_per_unit_time": "10"}}'
+# Agent applies to the job at the rate it proposes
+APPLY_TO_JOB='{"apply_to_job":{"job_id": "1", "proposed_rate": "10", "proposal_uri": "ipfs://bafybeihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$APPLY_TO_JOB" --from=$(echo $AGENT_INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# List the applications to the job
+LIST_APPLICATIONS='{"list_applications": {"job_id": "1", "start_after": null, "limit": 10}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$LIST_APPLICATIONS" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# User hires the agent, reject_application takes the same fields and also lets go of a hired agent that has not staked yet
+ACCEPT_APPLICATION='{"accept_application":{"job_id": "1", "agent": "inj1met6ppqdxvu4y6r2lf68uphty85hnz46qcv04u"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$ACCEPT_APPLICATION" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Agent withdraws its application, once unstaked when it was hired
+WITHDRAW_APPLICATION='{"withdraw_application":{"job_id": "1"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$WITHDRAW_APPLICATION" --from=$(echo $AGENT_INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# User hires 2 agents through a sealed-bid auction instead, bids are committed for a day then revealed for a day
+OPEN_AUCTION='{"open_auction":{"job_id": "1", "slots": 2, "commit_period": 86400, "reveal_period": 86400}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$OPEN_AUCTION" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Agent commits sha256("{job_id}:{bidder}:{rate}:{collateral}:{salt}") with a deposit covering the collateral
+# and the min agent stake, up to 10 bids per slot,
+# after giving the contract an allowance for the deposit
+COMMIT_BID='{"commit_bid":{"job_id": "1", "hash": "2tDJ8s3sSmN4KG9Vl+cqW3Zz0a4gm9u8L9KvLRn7WQo=", "deposit": "40"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$COMMIT_BID" --from=$(echo $AGENT_INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Agent reveals its bid once the commit period is over, a bid never revealed forfeits its deposit to the job owner
+REVEAL_BID='{"reveal_bid":{"job_id": "1", "rate": "8", "collateral": "30", "salt": "my-secret-salt"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$REVEAL_BID" --from=$(echo $AGENT_INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# List the bids on the auction of the job
+LIST_BIDS='{"list_bids": {"job_id": "1", "start_after": null, "limit": 10}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$LIST_BIDS" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Anyone settles the auction once the reveal period is over, the lowest rates are hired and staked
+SETTLE_AUCTION='{"settle_auction":{"job_id": "1"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$SETTLE_AUCTION" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Hired agent stakes to contract, it is paid the rate it proposed, collateral_asset defaults to the job's payment asset
+AGENT_STAKE='{"agent_stake":{"amount":"20", "job_id": "1", "collateral_asset": null}}'
 yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$AGENT_STAKE" --from=$(echo $AGENT_INJ_ADDRESS) \
 --chain-id="injective-888" \
 --yes --fees=1000000000000000inj --gas=2000000 \
@@ -110,6 +324,36 @@ injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q00
 --node=https://testnet.sentry.tm.injective.network:443 \
 --output json
 
+# Get the cost per unit time each agent quoted for the job, fixed once the job is started
+GET_AGENT_RATES='{"get_agent_rates": {"job_id": "1"}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GET_AGENT_RATES" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# User start the job once agents have staked, the agent set and their rates are fixed from here
+START_JOB='{"start_job":{"job_id": "1"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$START_JOB" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Agent submit the work
+SUBMIT_WORK='{"submit_work":{"job_id": "1"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$SUBMIT_WORK" --from=$(echo $AGENT_INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# User accept the submitted work, rewards can only be distributed after this (or open_dispute to contest it)
+COMPLETE_JOB='{"complete_job":{"job_id": "1"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$COMPLETE_JOB" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
 # distribute rewards from User to Agent by number of agents 
 # In case the task has a finite time -> the rewards is divided equally among the participating agents
 DISTRIBUTE_REWARDS_BY_AGENT='{"distribute_rewards_by_agent":{"job_id": "1"}}'
@@ -128,16 +372,129 @@ yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002
 --node=https://testnet.sentry.tm.injective.network:443 \
 --output json
 
-# Agent Juror role vote accept or Reject
-JUROR_VOTE='{"juror_vote":{"is_accept": "true"}}'
-yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$JUROR_VOTE" --from=$(echo $AGENT_INJ_ADDRESS) \
+# Distributions, dispute settlements and juror rewards credit balances, each recipient withdraws them
+GET_CLAIMABLE='{"get_claimable": {"addr": "inj1met6ppqdxvu4y6r2lf68uphty85hnz46qcv04u"}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GET_CLAIMABLE" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+WITHDRAW='{"withdraw":{"asset": {"cw20": {"addr": "inj1wp6x43895dewtfugkv08tvu7ajmvthzvel5mwn"}}, "amount": "30"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$WITHDRAW" --from=$(echo $AGENT_INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# User or Agent dispute the submitted work instead of completing the job
+# seed_hash = base64(sha256("<seed>"))
+SEED_HASH=$(printf '%s' "my-dispute-seed" | sha256sum | xxd -r -p | base64)
+OPEN_DISPUTE='{"open_dispute":{"job_id": "1", "reason": "summary misses half of the proposals", "seed_hash": "'$SEED_HASH'"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$OPEN_DISPUTE" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# The other side commits to its own seed before the commit period ends, or loses the dispute
+AGENT_SEED_HASH=$(printf '%s' "my-agent-seed" | sha256sum | xxd -r -p | base64)
+COMMIT_SEED='{"commit_seed":{"job_id": "1", "seed_hash": "'$AGENT_SEED_HASH'"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$COMMIT_SEED" --from=$(echo $AGENT_INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Both sides reveal their seed before the reveal period ends, a side that does not loses the dispute.
+# The second reveal draws the juror panel and starts the commit period
+REVEAL_SEED='{"reveal_seed":{"job_id": "1", "seed": "my-dispute-seed"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$REVEAL_SEED" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+REVEAL_SEED='{"reveal_seed":{"job_id": "1", "seed": "my-agent-seed"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$REVEAL_SEED" --from=$(echo $AGENT_INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Get dispute of a job
+GET_DISPUTE='{"get_dispute": {"job_id": "1"}}'
+injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GET_DISPUTE" \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Juror bond AWT to be allowed to vote (after increasing the allowance like above)
+# unregister_juror starts the unbonding period, withdraw_juror_bond pays the bond back once it is over
+REGISTER_JUROR='{"register_juror":{"amount": "100"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$REGISTER_JUROR" --from=$(echo $JUROR_INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Juror drawn on the panel commit a hashed vote during the commit period
+# hash = base64(sha256("<job_id>:<round>:<juror address>:<true|false>:<salt>")), keep the salt secret until the reveal
+VOTE_HASH=$(printf '%s' "1:0:$JUROR_INJ_ADDRESS:true:my-secret-salt" | sha256sum | xxd -r -p | base64)
+COMMIT_VOTE='{"commit_vote":{"job_id": "1", "hash": "'$VOTE_HASH'"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$COMMIT_VOTE" --from=$(echo $JUROR_INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Juror reveal the vote during the reveal period, unrevealed commits are not counted
+REVEAL_VOTE='{"reveal_vote":{"job_id": "1", "is_accept": true, "salt": "my-secret-salt"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$REVEAL_VOTE" --from=$(echo $JUROR_INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Anyone tally the round after the reveal period, the slashed bonds of incoherent jurors
+# are credited to the coherent jurors. Once the appeal period is over (or right away when the
+# round cannot be appealed) calling it again settles the dispute: accepted pays the agents,
+# rejected refunds the user and slashes the agents, tie or missing quorum refunds everybody.
+# The dispute fee is credited to the coherent jurors of the last round
+FINALIZE_DISPUTE='{"finalize_dispute":{"job_id": "1"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$FINALIZE_DISPUTE" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# Arbiter settles the dispute of a job whose escrow and agent collateral are within the arbiter limit of their asset,
+# before any round is tallied
+RESOLVE_DISPUTE='{"resolve_dispute":{"job_id": "1", "is_accept": true}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$RESOLVE_DISPUTE" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+
+# The losing side appeal a tallied round during the appeal period, a larger panel votes again.
+# The bond (doubled on every appeal) is paid back if the outcome is overturned.
+# The new panel is drawn like the first one: the appellant commits to a seed here,
+# then commit_seed and reveal_seed as above
+INCREASE_ALLOWANCE='{"increase_allowance":{"spender": "inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc", "amount":"100", "expires": null}}'
+yes 12345678 | injectived tx wasm execute inj1wp6x43895dewtfugkv08tvu7ajmvthzvel5mwn "$INCREASE_ALLOWANCE" --from=$(echo $INJ_ADDRESS) \
+--chain-id="injective-888" \
+--yes --fees=1000000000000000inj --gas=2000000 \
+--node=https://testnet.sentry.tm.injective.network:443 \
+--output json
+APPEAL_SEED_HASH=$(printf '%s' "my-appeal-seed" | sha256sum | xxd -r -p | base64)
+APPEAL='{"appeal":{"job_id": "1", "seed_hash": "'$APPEAL_SEED_HASH'"}}'
+yes 12345678 | injectived tx wasm execute inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$APPEAL" --from=$(echo $INJ_ADDRESS) \
 --chain-id="injective-888" \
 --yes --fees=1000000000000000inj --gas=2000000 \
 --node=https://testnet.sentry.tm.injective.network:443 \
 --output json
 
 # Get Vote result 
-GetVoteResult='{}'
+GetVoteResult='{"get_vote_result": {"job_id": "1"}}'
 injectived query wasm contract-state smart inj12xjw4pkv2trn5kah8lmu7a3ygprpu5q002egwc "$GetVoteResult" \
 --node=https://testnet.sentry.tm.injective.network:443 \
---output json 
\ No newline at end of file
+--output json
+
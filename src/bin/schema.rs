@@ -1,11 +1,12 @@
 use cosmwasm_schema::write_api;
 
-use injective_agent_work::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use injective_agent_work::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
         query: QueryMsg,
+        migrate: MigrateMsg,
     }
 }
@@ -1,39 +1,91 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
+use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
+    from_json, to_json_binary, Addr, Api, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Empty, Env,
+    MessageInfo, Order, Response, StdResult, Storage, Uint128, WasmMsg,
 };
-// use cw2::set_contract_version;
+use cw_storage_plus::Map;
+use cw2::set_contract_version;
+use cw_utils::Expiration;
+use semver::Version;
+
+use cw20::Cw20ReceiveMsg;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, VoteResultResponse};
+use crate::helpers::{bid_commitment, mix_entropy, panel_randomness, seed_commitment, vote_commitment};
+use crate::msg::{
+    AgentRateResponse, ApplicationResponse, AssetsResponse, BeaconQueryMsg, BidResponse, DisputeResponse, ExecuteMsg,
+    InstantiateMsg, JobResponse, JurorResponse, ListApplicationsResponse, ListBidsResponse, ListDisputesResponse,
+    ListJurorsResponse, MigrateMsg, QueryMsg, RandomnessResponse, ReceiveMsg,
+    RoleMembersResponse, TokenInfoResponse, VoteResultResponse,
+};
 use crate::state::*;
 
-/*
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:agent-work";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
-*/
+
+// `SettleAuction` sorts every bid at once, these bound it to at most 200 bids
+const MAX_AUCTION_SLOTS: u32 = 20;
+const MAX_BIDS_PER_SLOT: u32 = 10;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    let token_info = TokenInfo {
-        token_denom: msg.token_symbol,
-        token_address: msg.token_contract_addr,
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let config = Config {
+        treasury: msg.treasury.unwrap_or(info.sender.clone()),
+        protocol_fee_bps: msg.protocol_fee_bps,
     };
-    TOKEN_INFO.save(deps.storage, &token_info)?;
+    validate_config(deps.api, &config)?;
+    CONFIG.save(deps.storage, &config)?;
+    OWNERSHIP.save(deps.storage, &Ownership { owner: Some(info.sender), ..Ownership::default() })?;
+    if let Some(guardian) = msg.guardian {
+        let guardian = deps.api.addr_validate(guardian.as_str())?;
+        ROLES.save(deps.storage, (Role::Guardian.to_string(), guardian), &Empty {})?;
+    }
+    for token_info in msg.accepted_assets {
+        validate_asset(deps.api, &token_info.asset)?;
+        ACCEPTED_ASSETS.save(deps.storage, token_info.asset.to_string(), &token_info)?;
+    }
+    if !ACCEPTED_ASSETS.has(deps.storage, msg.bond_asset.to_string()) {
+        return Err(ContractError::AssetNotAccepted { asset: msg.bond_asset.to_string() });
+    }
+    BOND_ASSET.save(deps.storage, &msg.bond_asset)?;
+    for limit in msg.arbiter_max_escrow {
+        set_arbiter_max_escrow(deps.storage, limit)?;
+    }
+    let dispute_config = msg.dispute_config.unwrap_or_default();
+    validate_dispute_config(deps.api, &dispute_config)?;
+    DISPUTE_CONFIG.save(deps.storage, &dispute_config)?;
 
     Ok(Response::new().add_attribute("method", "instantiate"))
 }
 
+// create job
+// job lifecycle: start, submit, complete, dispute, cancel
 // user stake, unstake
 // agent stake, unstake
-// distribute rewards: send stake amount to agent
+// receive: user stake, agent stake or juror bond paid with a cw20 Send
+// distribute rewards: credit stake amount to agent
+// withdraw: pay out balances credited by distributions and dispute settlements
+// dispute panel drawn from the juror registry
+// juror register, unregister, commit and reveal vote
+// finalize dispute: tally a round, reward coherent jurors and slash the others,
+// then credit agents or refund the owner once the outcome can no longer be appealed
+// appeal: the losing side pays a bond for a new round with a larger panel
+// update config: fee manager sets the treasury and the protocol fee taken from agent rewards
+// update dispute config: juror manager sets bonds, panels and periods
+// propose, accept, renounce admin: two step handover of the admin
+// grant, revoke role: admin hands out the roles below
+// resolve dispute: arbiter settles the dispute of a small job without a juror vote
+// add, remove asset: admin manages the accepted payment assets
+// pause, unpause: guardian freezes staking, payouts, voting or withdrawals
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -41,500 +93,6025 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    let paused = PAUSED.may_load(deps.storage)?.unwrap_or_default();
+    if let Some(operation) = paused_operation(&paused, &msg) {
+        return Err(ContractError::Paused { operation: operation.to_string() });
+    }
     match msg {
+        ExecuteMsg::Receive(wrapper) => execute::receive(deps, env, info, wrapper),
+        ExecuteMsg::CreateJob { title, spec_uri, spec_hash, budget, payment_asset, min_agent_stake, max_agents } => {
+            execute::create_job(deps, env, info, title, spec_uri, spec_hash, budget, payment_asset, min_agent_stake, max_agents)
+        }
+        ExecuteMsg::StartJob { job_id } => execute::start_job(deps, info, job_id),
+        ExecuteMsg::SubmitWork { job_id } => execute::submit_work(deps, info, job_id),
+        ExecuteMsg::CompleteJob { job_id } => execute::complete_job(deps, info, job_id),
+        ExecuteMsg::OpenDispute { job_id, reason, seed_hash } => execute::open_dispute(deps, env, info, job_id, reason, seed_hash),
+        ExecuteMsg::CommitSeed { job_id, seed_hash } => execute::commit_seed(deps, env, info, job_id, seed_hash),
+        ExecuteMsg::RevealSeed { job_id, seed } => execute::reveal_seed(deps, env, info, job_id, seed),
+        ExecuteMsg::CancelJob { job_id } => execute::cancel_job(deps, info, job_id),
         ExecuteMsg::UserStake { amount, job_id} => execute::user_stake(deps, env, info, amount, job_id),
         ExecuteMsg::UserUnstake { amount, job_id} => execute::user_unstake(deps, info, amount, job_id),
-        ExecuteMsg::AgentStake { amount, job_id, cost_per_unit_time} => execute::agent_stake(deps, env, info, amount, job_id, cost_per_unit_time),
+        ExecuteMsg::AgentStake { amount, job_id, collateral_asset } => execute::agent_stake(deps, env, info, amount, job_id, collateral_asset),
         ExecuteMsg::AgentUnstake { amount, job_id} => execute::agent_unstake(deps, info, amount, job_id),
-        ExecuteMsg::DistributeRewardsByAgent {job_id} => execute::distribute_rewards_by_agent(deps, job_id),
-        ExecuteMsg::DistributeRewardsByTime {job_id} => execute::distribute_rewards_by_time(deps, job_id),
-        ExecuteMsg::JurorVote { is_accept } => execute::juror_vote(deps, info, is_accept),
-        ExecuteMsg::ResetVote {} => execute::reset_vote(deps),
+        ExecuteMsg::ApplyToJob { job_id, proposed_rate, proposal_uri } => execute::apply_to_job(deps, env, info, job_id, proposed_rate, proposal_uri),
+        ExecuteMsg::AcceptApplication { job_id, agent } => execute::accept_application(deps, info, job_id, agent),
+        ExecuteMsg::RejectApplication { job_id, agent } => execute::reject_application(deps, info, job_id, agent),
+        ExecuteMsg::WithdrawApplication { job_id } => execute::withdraw_application(deps, info, job_id),
+        ExecuteMsg::OpenAuction { job_id, slots, commit_period, reveal_period } => {
+            execute::open_auction(deps, env, info, job_id, slots, commit_period, reveal_period)
+        }
+        ExecuteMsg::CommitBid { job_id, hash, deposit } => execute::commit_bid(deps, env, info, job_id, hash, deposit),
+        ExecuteMsg::RevealBid { job_id, rate, collateral, salt } => execute::reveal_bid(deps, env, info, job_id, rate, collateral, salt),
+        ExecuteMsg::SettleAuction { job_id } => execute::settle_auction(deps, env, job_id),
+        ExecuteMsg::SetPayoutAuthority { job_id, authority } => execute::set_payout_authority(deps, info, job_id, authority),
+        ExecuteMsg::DistributeRewardsByAgent {job_id} => execute::distribute_rewards_by_agent(deps, info, job_id),
+        ExecuteMsg::DistributeRewardsByTime {job_id} => execute::distribute_rewards_by_time(deps, info, job_id),
+        ExecuteMsg::CommitVote { job_id, hash } => execute::commit_vote(deps, env, info, job_id, hash),
+        ExecuteMsg::RevealVote { job_id, is_accept, salt } => execute::reveal_vote(deps, env, info, job_id, is_accept, salt),
+        ExecuteMsg::FinalizeDispute { job_id } => execute::finalize_dispute(deps, env, job_id),
+        ExecuteMsg::Appeal { job_id, seed_hash } => execute::appeal(deps, env, info, job_id, seed_hash),
+        ExecuteMsg::RegisterJuror { amount } => execute::register_juror(deps, env, info, amount),
+        ExecuteMsg::UnregisterJuror {} => execute::unregister_juror(deps, env, info),
+        ExecuteMsg::WithdrawJurorBond {} => execute::withdraw_juror_bond(deps, env, info),
+        ExecuteMsg::Withdraw { asset, amount } => execute::withdraw(deps, info, asset, amount),
+        ExecuteMsg::UpdateConfig { treasury, protocol_fee_bps, arbiter_max_escrow } => execute::update_config(deps, info, treasury, protocol_fee_bps, arbiter_max_escrow),
+        ExecuteMsg::ProposeNewAdmin { addr, expiry } => execute::propose_new_admin(deps, env, info, addr, expiry),
+        ExecuteMsg::AcceptAdmin {} => execute::accept_admin(deps, env, info),
+        ExecuteMsg::RenounceAdmin {} => execute::renounce_admin(deps, info),
+        ExecuteMsg::UpdateDisputeConfig { dispute_config } => execute::update_dispute_config(deps, info, dispute_config),
+        ExecuteMsg::GrantRole { role, addr } => execute::grant_role(deps, info, role, addr),
+        ExecuteMsg::RevokeRole { role, addr } => execute::revoke_role(deps, info, role, addr),
+        ExecuteMsg::ResolveDispute { job_id, is_accept } => execute::resolve_dispute(deps, info, job_id, is_accept),
+        ExecuteMsg::Pause { flags } => execute::pause(deps, env, info, flags),
+        ExecuteMsg::Unpause { flags } => execute::unpause(deps, env, info, flags),
+        ExecuteMsg::AddAsset { token_denom, asset } => execute::add_asset(deps, info, token_denom, asset),
+        ExecuteMsg::RemoveAsset { asset } => execute::remove_asset(deps, info, asset),
     }
 }
 
 pub mod execute {
     use super::*;
-    use cosmwasm_std::{to_json_binary, CosmosMsg, WasmMsg};
+    use cosmwasm_std::Event;
 
-    pub fn user_stake(
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_job(
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        amount: Uint128,
-        job_id: Uint128
+        title: String,
+        spec_uri: String,
+        spec_hash: Option<String>,
+        budget: Uint128,
+        payment_asset: Asset,
+        min_agent_stake: Uint128,
+        max_agents: Option<u32>,
     ) -> Result<Response, ContractError> {
-        if let Some(job_owner) = JOB_OWNER.may_load(deps.storage, job_id.to_string())? {
-            if job_owner != info.sender {
-                return Err(ContractError::NotJobOwner {});
-            }
-        } else {
-            JOB_OWNER.save(deps.storage, job_id.to_string(), &info.sender)?;
+        ensure_accepted_asset(deps.as_ref(), &payment_asset)?;
+        // jobs migrated from the first deployment kept the ids their stakers picked
+        let mut job_id = JOB_COUNT.may_load(deps.storage)?.unwrap_or_default() + Uint128::one();
+        while JOBS.has(deps.storage, job_key(job_id)?) {
+            job_id += Uint128::one();
         }
-        
-
-        let token_info = TOKEN_INFO.load(deps.storage)?;
-        let mut user_stake_amount = USER_STAKE
-            .load(deps.storage, (info.sender.clone(), job_id.to_string()))
-            .unwrap_or(Uint128::zero());
-        user_stake_amount += amount;
-        USER_STAKE.save(deps.storage, (info.sender.clone(), job_id.to_string()), &user_stake_amount)?;
+        JOB_COUNT.save(deps.storage, &job_id)?;
 
-        let transfer_from_msg = cw20::Cw20ExecuteMsg::TransferFrom {
-            owner: info.sender.to_string(),
-            recipient: env.contract.address.to_string(),
-            amount,
+        let job = Job {
+            id: job_id,
+            owner: info.sender.clone(),
+            title,
+            spec_uri,
+            spec_hash,
+            budget,
+            payment_asset,
+            created_at: env.block.time,
+            status: JobStatus::Open,
+            payout_authority: None,
+            min_agent_stake,
+            max_agents,
         };
-
-        let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: token_info.token_address.to_string(),
-            msg: to_json_binary(&transfer_from_msg)?,
-            funds: info.funds,
-        });
+        JOBS.save(deps.storage, job_key(job_id)?, &job)?;
 
         Ok(Response::new()
-            .add_attribute("action", "user stake")
-            .add_message(msg))
+            .add_attribute("action", "create job")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("owner", info.sender))
     }
 
-    pub fn user_unstake(
+    pub fn start_job(
         deps: DepsMut,
         info: MessageInfo,
-        amount: Uint128,
         job_id: Uint128
     ) -> Result<Response, ContractError> {
-        let job_owner = JOB_OWNER.load(deps.storage, job_id.to_string()).unwrap_or(info.sender.clone());
-        if job_owner != info.sender {
+        let mut job = load_job(deps.as_ref(), job_id)?;
+        if job.owner != info.sender {
             return Err(ContractError::NotJobOwner {});
         }
+        ensure_job_status(&job, &[JobStatus::Open])?;
+        ensure_no_auction(deps.storage, job_id)?;
+        if job_agents(deps.storage, job_id)?.is_empty() {
+            return Err(ContractError::NoAgents {});
+        }
 
-        let token_info = TOKEN_INFO.load(deps.storage)?;
-        let mut user_stake_amount = USER_STAKE
-            .load(deps.storage, (info.sender.clone(), job_id.to_string()))
-            .unwrap_or(Uint128::zero());
-        if user_stake_amount < amount {
-            return Err(ContractError::InsufficientStake {});
-        } else {
-            user_stake_amount -= amount;
-        };
-        USER_STAKE.save(deps.storage, (info.sender.clone(), job_id.to_string()), &user_stake_amount)?;
-        
+        job.status = JobStatus::InProgress;
+        JOBS.save(deps.storage, job_key(job_id)?, &job)?;
 
-        let transfer_msg = cw20::Cw20ExecuteMsg::Transfer {
-            recipient: info.sender.to_string(),
-            amount,
-        };
+        Ok(Response::new()
+            .add_attribute("action", "start job")
+            .add_attribute("job_id", job_id.to_string()))
+    }
 
-        let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: token_info.token_address.to_string(),
-            msg: to_json_binary(&transfer_msg)?,
-            funds: info.funds,
-        });
+    pub fn submit_work(
+        deps: DepsMut,
+        info: MessageInfo,
+        job_id: Uint128
+    ) -> Result<Response, ContractError> {
+        let mut job = load_job(deps.as_ref(), job_id)?;
+        if !is_job_agent(deps.as_ref(), job_id, &info.sender)? {
+            return Err(ContractError::NotJobAgent {});
+        }
+        ensure_job_status(&job, &[JobStatus::InProgress])?;
+
+        job.status = JobStatus::Submitted;
+        JOBS.save(deps.storage, job_key(job_id)?, &job)?;
 
         Ok(Response::new()
-            .add_attribute("action", "user unstake")
-            .add_message(msg))
+            .add_attribute("action", "submit work")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("agent", info.sender))
     }
 
-    pub fn agent_stake(
+    pub fn complete_job(
+        deps: DepsMut,
+        info: MessageInfo,
+        job_id: Uint128
+    ) -> Result<Response, ContractError> {
+        let mut job = load_job(deps.as_ref(), job_id)?;
+        if job.owner != info.sender {
+            return Err(ContractError::NotJobOwner {});
+        }
+        ensure_job_status(&job, &[JobStatus::Submitted])?;
+
+        job.status = JobStatus::Completed;
+        JOBS.save(deps.storage, job_key(job_id)?, &job)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "complete job")
+            .add_attribute("job_id", job_id.to_string()))
+    }
+
+    pub fn open_dispute(
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        amount: Uint128,
         job_id: Uint128,
-        cost_per_unit_time: Uint128
+        reason: String,
+        seed_hash: Binary,
     ) -> Result<Response, ContractError> {
-        let token_info = TOKEN_INFO.load(deps.storage)?;
-        let mut agent_stake_amount = AGENT_STAKE
-            .load(deps.storage, (info.sender.clone(), job_id.to_string()))
-            .unwrap_or(Uint128::zero());
-        agent_stake_amount += amount;
-        AGENT_STAKE.save(deps.storage, (info.sender.clone(), job_id.to_string()), &agent_stake_amount)?;
-        JOB_AGENT.update(deps.storage, job_id.to_string(), |agents| -> StdResult<_> {
-            let mut agents = agents.unwrap_or(vec![]);
-            agents.push(info.sender.clone());
-            Ok(agents)
-        })?;
-        AGENT_COST.save(deps.storage, info.sender.clone(), &cost_per_unit_time)?;
+        let mut job = load_job(deps.as_ref(), job_id)?;
+        if job.owner != info.sender && !is_job_agent(deps.as_ref(), job_id, &info.sender)? {
+            return Err(ContractError::Unauthorized {});
+        }
+        ensure_job_status(&job, &[JobStatus::Submitted])?;
 
-        let transfer_from_msg = cw20::Cw20ExecuteMsg::TransferFrom {
-            owner: info.sender.to_string(),
-            recipient: env.contract.address.to_string(),
-            amount,
-        };
+        job.status = JobStatus::Disputed;
+        JOBS.save(deps.storage, job_key(job_id)?, &job)?;
 
-        let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: token_info.token_address.to_string(),
-            msg: to_json_binary(&transfer_from_msg)?,
-            funds: info.funds,
-        });
+        // checked now, a side could not be blamed for a draw that cannot happen
+        if eligible_jurors(deps.as_ref(), &job)?.is_empty() {
+            return Err(ContractError::NoEligibleJurors {});
+        }
+        let dispute_config = DISPUTE_CONFIG.load(deps.storage)?;
+        let commit_deadline = env.block.time.plus_seconds(dispute_config.commit_period);
+        let draw = PanelDraw {
+            first_party: info.sender.clone(),
+            first_hash: seed_hash,
+            second_hash: None,
+            first_seed: None,
+            second_seed: None,
+            beacon: None,
+            deadline: commit_deadline,
+        };
+        let dispute = Dispute {
+            job_id,
+            opener: info.sender.clone(),
+            reason,
+            draw: Some(draw),
+            round: 0,
+            panel: vec![],
+            commits: Uint128::zero(),
+            accept_votes: Uint128::zero(),
+            reject_votes: Uint128::zero(),
+            accept_weight: Uint128::zero(),
+            reject_weight: Uint128::zero(),
+            opened_at: env.block.time,
+            commit_deadline,
+            reveal_deadline: commit_deadline.plus_seconds(dispute_config.reveal_period),
+            rounds: vec![],
+            appeal_deadline: None,
+            outcome: None,
+        };
+        DISPUTES.save(deps.storage, job_key(job_id)?, &dispute)?;
+        OPEN_DISPUTES.save(deps.storage, job_key(job_id)?, &Empty {})?;
 
         Ok(Response::new()
-            .add_attribute("action", "agent stake")
-            .add_message(msg))
+            .add_attribute("action", "open dispute")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("opener", info.sender)
+            .add_attribute("draw_deadline", commit_deadline.seconds().to_string()))
     }
 
-    pub fn agent_unstake(
+    pub fn commit_seed(
         deps: DepsMut,
+        env: Env,
         info: MessageInfo,
-        amount: Uint128,
-        job_id: Uint128
+        job_id: Uint128,
+        seed_hash: Binary,
     ) -> Result<Response, ContractError> {
-        let token_info = TOKEN_INFO.load(deps.storage)?;
-        let mut agent_stake_amount = AGENT_STAKE
-            .load(deps.storage, (info.sender.clone(), job_id.to_string()))
-            .unwrap_or(Uint128::zero());
-        if agent_stake_amount < amount {
-            return Err(ContractError::InsufficientStake {});
-        } else {
-            agent_stake_amount -= amount;
+        let mut dispute = DISPUTES
+            .may_load(deps.storage, job_key(job_id)?)?
+            .ok_or(ContractError::DisputeNotFound { job_id })?;
+        let Some(draw) = dispute.draw.as_mut() else {
+            return Err(ContractError::PanelAlreadyDrawn {});
         };
-        AGENT_STAKE.save(deps.storage, (info.sender.clone(), job_id.to_string()), &agent_stake_amount)?;
-        JOB_AGENT.update(deps.storage, job_id.to_string(), |agents| -> StdResult<_> {
-            let mut agents = agents.unwrap_or(vec![]);
-            agents.retain(|agent| agent != &info.sender);
-            Ok(agents)
-        })?;
-
-        let transfer_msg = cw20::Cw20ExecuteMsg::Transfer {
-            recipient: info.sender.to_string(),
-            amount,
+        if env.block.time >= draw.deadline {
+            return Err(ContractError::VotingClosed {});
+        }
+        if draw.second_hash.is_some() {
+            return Err(ContractError::SeedAlreadyCommitted {});
+        }
+        let job = load_job(deps.as_ref(), job_id)?;
+        let is_other_side = if draw.first_party == job.owner {
+            is_job_agent(deps.as_ref(), job_id, &info.sender)?
+        } else {
+            job.owner == info.sender
         };
+        if !is_other_side {
+            return Err(ContractError::Unauthorized {});
+        }
+        // a copied commitment could only be opened by the first party
+        if seed_hash == draw.first_hash {
+            return Err(ContractError::InvalidSeed {});
+        }
 
-        let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: token_info.token_address.to_string(),
-            msg: to_json_binary(&transfer_msg)?,
-            funds: info.funds,
-        });
+        // read while both seeds are fixed and neither is known
+        let dispute_config = DISPUTE_CONFIG.load(deps.storage)?;
+        if let Some(beacon) = &dispute_config.randomness_beacon {
+            draw.beacon = Some(query_beacon(deps.as_ref(), beacon)?);
+        }
+        draw.second_hash = Some(seed_hash);
+        draw.deadline = env.block.time.plus_seconds(dispute_config.reveal_period);
+        let draw_deadline = draw.deadline;
+        DISPUTES.save(deps.storage, job_key(job_id)?, &dispute)?;
 
         Ok(Response::new()
-            .add_attribute("action", "agent unstake")
-            .add_message(msg))
+            .add_attribute("action", "commit seed")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("party", info.sender)
+            .add_attribute("draw_deadline", draw_deadline.seconds().to_string()))
     }
 
-    pub fn distribute_rewards_by_agent(
+    pub fn reveal_seed(
         deps: DepsMut,
-        job_id: Uint128
+        env: Env,
+        info: MessageInfo,
+        job_id: Uint128,
+        seed: String,
     ) -> Result<Response, ContractError> {
-        let token_info = TOKEN_INFO.load(deps.storage)?;
-        let job_owner_addr = JOB_OWNER.load(deps.storage, job_id.to_string()).unwrap();
-        let mut rewards_owner_stake_amount = USER_STAKE
-            .load(deps.storage, (job_owner_addr.clone(), job_id.to_string()))
-            .unwrap_or(Uint128::zero());
-        let job_agent_addrs = JOB_AGENT.load(deps.storage, job_id.to_string()).unwrap();
-        let rewards_per_agent =
-            rewards_owner_stake_amount / Uint128::from(job_agent_addrs.len() as u128);
-        rewards_owner_stake_amount = Uint128::zero();
-        USER_STAKE.save(
-            deps.storage,
-            (job_owner_addr.clone(), job_id.to_string()),
-            &rewards_owner_stake_amount,
-        )?;
-        let mut messages: Vec<CosmosMsg> = vec![];
-        for agent_addr in job_agent_addrs {
-            // repay staked amount for agent
-            let mut agent_stake_amount = AGENT_STAKE
-                .load(deps.storage, ((agent_addr).clone(), job_id.to_string()))
-                .unwrap_or(Uint128::zero());
-            let repay_amount = agent_stake_amount + rewards_per_agent;
-            agent_stake_amount = Uint128::zero();
-            AGENT_STAKE.save(deps.storage, (agent_addr.clone(), job_id.to_string()), &agent_stake_amount)?;
-
-            // send rewards to agent
-            let transfer_msg = cw20::Cw20ExecuteMsg::Transfer {
-                recipient: agent_addr.to_string(),
-                amount: repay_amount,
-            };
+        let mut dispute = DISPUTES
+            .may_load(deps.storage, job_key(job_id)?)?
+            .ok_or(ContractError::DisputeNotFound { job_id })?;
+        let Some(mut draw) = dispute.draw.take() else {
+            return Err(ContractError::PanelAlreadyDrawn {});
+        };
+        // a seed revealed before the other side committed would let it pick its own
+        if draw.second_hash.is_none() || env.block.time >= draw.deadline {
+            return Err(ContractError::NotSeedRevealPeriod {});
+        }
+        let job = load_job(deps.as_ref(), job_id)?;
+        if job.owner != info.sender && !is_job_agent(deps.as_ref(), job_id, &info.sender)? {
+            return Err(ContractError::Unauthorized {});
+        }
+        let seed_hash = seed_commitment(&seed);
+        let revealed = if seed_hash == draw.first_hash {
+            &mut draw.first_seed
+        } else if draw.second_hash.as_ref() == Some(&seed_hash) {
+            &mut draw.second_seed
+        } else {
+            return Err(ContractError::InvalidSeed {});
+        };
+        if revealed.is_some() {
+            return Err(ContractError::SeedAlreadyRevealed {});
+        }
+        *revealed = Some(seed);
 
-            let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: token_info.token_address.to_string(),
-                msg: to_json_binary(&transfer_msg)?,
-                funds: vec![],
-            });
-            messages.push(msg);
+        let mut response = Response::new()
+            .add_attribute("action", "reveal seed")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("party", info.sender);
+        if let (Some(first_seed), Some(second_seed)) = (&draw.first_seed, &draw.second_seed) {
+            let dispute_config = DISPUTE_CONFIG.load(deps.storage)?;
+            let entropy = draw.beacon.clone().unwrap_or_default();
+            let seed = mix_entropy(&mix_entropy(&entropy, first_seed), second_seed);
+            // jurors may have left since the dispute was opened, an empty panel ends without quorum
+            let candidates = eligible_jurors(deps.as_ref(), &job)?;
+            dispute.panel = draw_panel(candidates, job_id, seed.as_slice(), dispute_config.panel_size_for(dispute.round));
+            // voting starts once the panel is known
+            dispute.commit_deadline = env.block.time.plus_seconds(dispute_config.commit_period);
+            dispute.reveal_deadline = dispute.commit_deadline.plus_seconds(dispute_config.reveal_period);
+            response = response
+                .add_attribute("panel", dispute.panel.iter().map(Addr::as_str).collect::<Vec<_>>().join(","))
+                .add_attribute("commit_deadline", dispute.commit_deadline.seconds().to_string())
+                .add_attribute("reveal_deadline", dispute.reveal_deadline.seconds().to_string());
+        } else {
+            dispute.draw = Some(draw);
         }
-        Ok(Response::new()
-            .add_attribute("action", "distribution rewards by agent")
-            .add_messages(messages))
+        DISPUTES.save(deps.storage, job_key(job_id)?, &dispute)?;
+
+        Ok(response)
     }
 
-    pub fn distribute_rewards_by_time(
+    pub fn cancel_job(
         deps: DepsMut,
+        info: MessageInfo,
         job_id: Uint128
     ) -> Result<Response, ContractError> {
-        let token_info = TOKEN_INFO.load(deps.storage)?;
-        let job_owner_addr = JOB_OWNER.load(deps.storage, job_id.to_string()).unwrap();
-        let mut rewards_owner_stake_amount = USER_STAKE
-            .load(deps.storage, (job_owner_addr.clone(), job_id.to_string()))
-            .unwrap_or(Uint128::zero());
-        let job_agent_addrs = JOB_AGENT.load(deps.storage, job_id.to_string()).unwrap(); 
-        if !query::check_if_enough_rewards(
-            deps.as_ref(),
-            job_id
-        ) {
-            return Err(ContractError::InsufficientStake {});
+        let mut job = load_job(deps.as_ref(), job_id)?;
+        if job.owner != info.sender {
+            return Err(ContractError::NotJobOwner {});
         }
-        let mut messages: Vec<CosmosMsg> = vec![];
-        let mut total_cost_per_unit_time = Uint128::zero();
-        for agent_addr in job_agent_addrs {
-            let agent_cost = AGENT_COST.load(deps.storage, agent_addr.clone()).unwrap();
-            total_cost_per_unit_time += agent_cost;
+        ensure_job_status(&job, &[JobStatus::Open])?;
 
-            // send rewards to agent
-            let transfer_msg = cw20::Cw20ExecuteMsg::Transfer {
-                recipient: agent_addr.to_string(),
-                amount: agent_cost,
-            };
+        job.status = JobStatus::Cancelled;
+        JOBS.save(deps.storage, job_key(job_id)?, &job)?;
 
-            let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: token_info.token_address.to_string(),
-                msg: to_json_binary(&transfer_msg)?,
-                funds: vec![],
-            });
-            messages.push(msg);
-        }
-        rewards_owner_stake_amount -= total_cost_per_unit_time;
-        USER_STAKE.save(
-            deps.storage,
-            (job_owner_addr.clone(), job_id.to_string()),
-            &rewards_owner_stake_amount,
-        )?;
         Ok(Response::new()
-            .add_attribute("action", "distribution rewards by time unit")
-            .add_messages(messages))
+            .add_attribute("action", "cancel job")
+            .add_attribute("job_id", job_id.to_string()))
     }
 
-    pub fn juror_vote(
+    pub fn set_payout_authority(
         deps: DepsMut,
         info: MessageInfo,
-        is_accept: bool,
+        job_id: Uint128,
+        authority: Option<Addr>
     ) -> Result<Response, ContractError> {
-        let accept_vote = ACCEPT_VOTE.load(deps.storage).unwrap_or(Uint128::zero());
-        let reject_vote = REJECT_VOTE.load(deps.storage).unwrap_or(Uint128::zero());
-        let is_juror_voted = IS_JUROR_VOTED
-            .load(deps.storage, info.sender.clone())
-            .unwrap_or(false);
-        if is_juror_voted {
-            return Err(ContractError::AlreadyVoted {});
-        } else {
-            IS_JUROR_VOTED.save(deps.storage, info.sender.clone(), &true)?;
-        }
-        if is_accept {
-            ACCEPT_VOTE.save(deps.storage, &(accept_vote + Uint128::new(1)))?;
-        } else {
-            REJECT_VOTE.save(deps.storage, &(reject_vote + Uint128::new(1)))?;
+        let mut job = load_job(deps.as_ref(), job_id)?;
+        if job.owner != info.sender {
+            return Err(ContractError::NotJobOwner {});
         }
-        Ok(Response::new().add_attribute("action", "juror vote"))
-    }
 
-    pub fn reset_vote(deps: DepsMut) -> Result<Response, ContractError> {
-        ACCEPT_VOTE.save(deps.storage, &Uint128::zero())?;
-        REJECT_VOTE.save(deps.storage, &Uint128::zero())?;
-        IS_JUROR_VOTED.clear(deps.storage);
-        Ok(Response::new().add_attribute("action", "reset vote"))
+        job.payout_authority = authority
+            .map(|addr| deps.api.addr_validate(addr.as_str()))
+            .transpose()?;
+        JOBS.save(deps.storage, job_key(job_id)?, &job)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set payout authority")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute(
+                "authority",
+                job.payout_authority.map(String::from).unwrap_or_default(),
+            ))
     }
-}
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetUserStake { user_addr , job_id} => query::get_user_stake(deps, user_addr, job_id),
-        QueryMsg::GetAgentStake { agent_addr , job_id} => query::get_agent_stake(deps, agent_addr, job_id),
-        QueryMsg::GetNumOfAgent { job_id } => query::get_num_of_agent(deps, job_id),
-        QueryMsg::GetTokenInfo {} => query::get_token_info(deps),
-        QueryMsg::CheckIfEnoughRewards {job_id} => to_json_binary(&query::check_if_enough_rewards(
-            deps,
-            job_id
-        )),
-        QueryMsg::GetVoteResult {} => query::get_vote_result(deps),
+    // tokens sent with `Cw20ExecuteMsg::Send` are already held by the contract,
+    // so the hooks only do the bookkeeping
+    pub fn receive(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        wrapper: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let asset = Asset::Cw20 { addr: info.sender };
+        let bond_asset = BOND_ASSET.load(deps.storage)?;
+        if !ACCEPTED_ASSETS.has(deps.storage, asset.to_string()) && asset != bond_asset {
+            return Err(ContractError::Unauthorized {});
+        }
+        let sender = deps.api.addr_validate(&wrapper.sender)?;
+        match from_json(&wrapper.msg)? {
+            ReceiveMsg::FundJob { job_id } => add_user_stake(deps, sender, asset, wrapper.amount, job_id),
+            ReceiveMsg::AgentStake { job_id } => add_agent_stake(deps, sender, asset, wrapper.amount, job_id),
+            ReceiveMsg::BondJuror {} => {
+                if asset != bond_asset {
+                    return Err(ContractError::WrongAsset { expected: bond_asset.to_string() });
+                }
+                bond_juror(deps, env, sender, wrapper.amount)
+            }
+            ReceiveMsg::CommitBid { job_id, hash } => add_bid(deps, env, sender, asset, job_id, hash, wrapper.amount),
+        }
     }
-}
 
-pub mod query {
-    use super::*;
+    pub fn user_stake(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+        job_id: Uint128
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        let payment = collect_payment(&job.payment_asset, &info, &env.contract.address, amount)?;
+        let response = add_user_stake(deps, info.sender.clone(), job.payment_asset, amount, job_id)?;
 
-    pub fn get_user_stake(deps: Deps, user_addr: Addr, job_id: Uint128) -> StdResult<Binary> {
-        let user_stake_amount = USER_STAKE
-            .load(deps.storage, (user_addr, job_id.to_string()))
-            .unwrap_or(Uint128::zero());
-        to_json_binary(&user_stake_amount)
+        Ok(response.add_messages(payment))
     }
 
-    pub fn get_agent_stake(deps: Deps, agent_addr: Addr, job_id: Uint128) -> StdResult<Binary> {
-        let agent_stake_amount = AGENT_STAKE
-            .load(deps.storage, (agent_addr, job_id.to_string()))
-            .unwrap_or(Uint128::zero());
-        to_json_binary(&agent_stake_amount)
-    }
+    fn add_user_stake(
+        deps: DepsMut,
+        sender: Addr,
+        asset: Asset,
+        amount: Uint128,
+        job_id: Uint128,
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        if job.owner != sender {
+            return Err(ContractError::NotJobOwner {});
+        }
+        ensure_job_status(&job, &[JobStatus::Open])?;
+        if asset != job.payment_asset {
+            return Err(ContractError::WrongAsset { expected: job.payment_asset.to_string() });
+        }
 
-    pub fn get_num_of_agent(deps: Deps, job_id: Uint128) -> StdResult<Binary> {
-        let job_agent_addrs = JOB_AGENT.load(deps.storage, job_id.to_string()).unwrap();
-        to_json_binary(&Uint128::new(job_agent_addrs.len() as u128))
-    }
+        let mut user_stake = USER_STAKE
+            .may_load(deps.storage, (sender.clone(), job_key(job_id)?))?
+            .unwrap_or(AssetAmount { asset, amount: Uint128::zero() });
+        user_stake.amount += amount;
+        USER_STAKE.save(deps.storage, (sender.clone(), job_key(job_id)?), &user_stake)?;
 
-    pub fn get_token_info(deps: Deps) -> StdResult<Binary> {
-        let token_info = TOKEN_INFO.load(deps.storage)?;
-        to_json_binary(&token_info)
+        Ok(Response::new()
+            .add_attribute("action", "user stake")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("amount", amount))
     }
 
-    pub fn check_if_enough_rewards(
-        deps: Deps,
+    pub fn user_unstake(
+        deps: DepsMut,
+        info: MessageInfo,
+        amount: Uint128,
         job_id: Uint128
-    ) -> bool {
-        let job_owner_addr = JOB_OWNER.load(deps.storage, job_id.to_string()).unwrap();
-        let job_owner_addrs = JOB_AGENT.load(deps.storage, job_id.to_string()).unwrap();
-        let rewards_owner_stake_amount = USER_STAKE
-            .load(deps.storage, (job_owner_addr, job_id.to_string()))
-            .unwrap_or(Uint128::zero());
-        let mut total_cost_per_unit_time = Uint128::zero();
-        for agent_addr in job_owner_addrs {
-            let agent_cost = AGENT_COST.load(deps.storage, agent_addr.clone()).unwrap();
-            total_cost_per_unit_time += agent_cost;
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        if job.owner != info.sender {
+            return Err(ContractError::NotJobOwner {});
+        }
+        // agents may leave a completed job before it pays out, the escrow then goes back to the owner
+        if job.status != JobStatus::Completed || !job_agents(deps.storage, job_id)?.is_empty() {
+            ensure_job_status(&job, &[JobStatus::Open, JobStatus::Cancelled])?;
         }
-        rewards_owner_stake_amount >= total_cost_per_unit_time
-    }
 
-    pub fn get_vote_result(deps: Deps) -> StdResult<Binary> {
-        let accept_vote = ACCEPT_VOTE.load(deps.storage).unwrap_or(Uint128::zero());
-        let reject_vote = REJECT_VOTE.load(deps.storage).unwrap_or(Uint128::zero());
-        let vote_result = VoteResultResponse {
-            accept_vote,
-            reject_vote,
+        let mut user_stake = USER_STAKE
+            .may_load(deps.storage, (info.sender.clone(), job_key(job_id)?))?
+            .unwrap_or(AssetAmount { asset: job.payment_asset, amount: Uint128::zero() });
+        if user_stake.amount < amount {
+            return Err(ContractError::InsufficientStake {});
+        } else {
+            user_stake.amount -= amount;
         };
-        to_json_binary(&vote_result)
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use std::usize;
+        USER_STAKE.save(deps.storage, (info.sender.clone(), job_key(job_id)?), &user_stake)?;
+        
 
-    use cosmwasm_std::{Addr, Uint128};
-    use cw_multi_test::{App, BankKeeper, ContractWrapper, Executor, IntoAddr};
+        Ok(Response::new()
+            .add_attribute("action", "user unstake")
+            .add_message(transfer_msg(&user_stake.asset, &info.sender, amount)?))
+    }
 
-    use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
-    use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
+    pub fn agent_stake(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+        job_id: Uint128,
+        collateral_asset: Option<Asset>,
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        let asset = collateral_asset.unwrap_or(job.payment_asset);
+        let payment = collect_payment(&asset, &info, &env.contract.address, amount)?;
+        let response = add_agent_stake(deps, info.sender.clone(), asset, amount, job_id)?;
 
-    use super::*;
+        Ok(response.add_messages(payment))
+    }
 
-    fn setup_cw20_contract(app: &mut App, admin: Addr) -> Addr {
-        let cw20_code = ContractWrapper::new(
-            cw20_base::contract::execute,
-            cw20_base::contract::instantiate,
-            cw20_base::contract::query,
-        );
-        let cw20_code_id = app.store_code(Box::new(cw20_code));
+    fn add_agent_stake(
+        deps: DepsMut,
+        sender: Addr,
+        asset: Asset,
+        amount: Uint128,
+        job_id: Uint128,
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        ensure_job_status(&job, &[JobStatus::Open])?;
+        ensure_accepted_asset(deps.as_ref(), &asset)?;
+        // only agents the owner hired join, at the rate they proposed
+        let application = APPLICATIONS
+            .may_load(deps.storage, (job_key(job_id)?, sender.clone()))?
+            .filter(|application| application.status == ApplicationStatus::Accepted)
+            .ok_or(ContractError::NotHired {})?;
 
-        let cw20_addr = app
-            .instantiate_contract(
-                cw20_code_id,
-                admin.clone(),
-                &Cw20InstantiateMsg {
-                    name: "Test Token".to_string(),
-                    symbol: "TTK".to_string(),
-                    decimals: 6,
-                    initial_balances: vec![Cw20Coin {
-                        address: admin.to_string(),
-                        amount: Uint128::new(1000000),
-                    }],
-                    mint: None,
-                    marketing: None,
-                },
-                &[],
-                "CW20 Test Token",
-                None,
-            )
-            .unwrap();
+        // top ups must stay in the asset of the first stake
+        let mut agent_stake = AGENT_STAKE
+            .may_load(deps.storage, (sender.clone(), job_key(job_id)?))?
+            .unwrap_or(AssetAmount { asset: asset.clone(), amount: Uint128::zero() });
+        if agent_stake.asset != asset {
+            return Err(ContractError::WrongAsset { expected: agent_stake.asset.to_string() });
+        }
+        agent_stake.amount += amount;
+        if agent_stake.amount.is_zero() || agent_stake.amount < job.min_agent_stake {
+            return Err(ContractError::BelowMinAgentStake { min_agent_stake: job.min_agent_stake });
+        }
+        AGENT_STAKE.save(deps.storage, (sender.clone(), job_key(job_id)?), &agent_stake)?;
+        // a top up keeps the slot
+        let slot = AgentSlot { cost_per_unit_time: application.proposed_rate };
+        JOB_AGENTS.save(deps.storage, (job_key(job_id)?, sender.clone()), &slot)?;
 
-        cw20_addr
+        Ok(Response::new()
+            .add_attribute("action", "agent stake")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("amount", amount))
+    }
+
+    pub fn agent_unstake(
+        deps: DepsMut,
+        info: MessageInfo,
+        amount: Uint128,
+        job_id: Uint128
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        ensure_job_status(&job, &[JobStatus::Open, JobStatus::Completed, JobStatus::Cancelled])?;
+
+        let mut agent_stake = AGENT_STAKE
+            .may_load(deps.storage, (info.sender.clone(), job_key(job_id)?))?
+            .unwrap_or(AssetAmount { asset: job.payment_asset, amount: Uint128::zero() });
+        if agent_stake.amount < amount {
+            return Err(ContractError::InsufficientStake {});
+        } else {
+            agent_stake.amount -= amount;
+        };
+        // the agent stays on the job until it unstakes everything
+        if agent_stake.amount.is_zero() {
+            JOB_AGENTS.remove(deps.storage, (job_key(job_id)?, info.sender.clone()));
+        } else if agent_stake.amount < job.min_agent_stake {
+            return Err(ContractError::BelowMinAgentStake { min_agent_stake: job.min_agent_stake });
+        }
+        AGENT_STAKE.save(deps.storage, (info.sender.clone(), job_key(job_id)?), &agent_stake)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "agent unstake")
+            .add_message(transfer_msg(&agent_stake.asset, &info.sender, amount)?))
+    }
+
+    pub fn apply_to_job(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        job_id: Uint128,
+        proposed_rate: Uint128,
+        proposal_uri: String,
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        ensure_job_status(&job, &[JobStatus::Open])?;
+        ensure_no_auction(deps.storage, job_id)?;
+        let key = (job_key(job_id)?, info.sender.clone());
+        if APPLICATIONS
+            .may_load(deps.storage, key.clone())?
+            .is_some_and(|application| application.status != ApplicationStatus::Rejected)
+        {
+            return Err(ContractError::AlreadyApplied {});
+        }
+        let application = Application {
+            proposed_rate,
+            proposal_uri,
+            applied_at: env.block.time,
+            status: ApplicationStatus::Pending,
+        };
+        APPLICATIONS.save(deps.storage, key, &application)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "apply to job")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("agent", info.sender)
+            .add_attribute("proposed_rate", proposed_rate))
+    }
+
+    pub fn accept_application(
+        deps: DepsMut,
+        info: MessageInfo,
+        job_id: Uint128,
+        agent: Addr,
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        if job.owner != info.sender {
+            return Err(ContractError::NotJobOwner {});
+        }
+        ensure_job_status(&job, &[JobStatus::Open])?;
+        ensure_no_auction(deps.storage, job_id)?;
+        let mut application = load_pending_application(deps.as_ref(), job_id, &agent)?;
+        let hires = JOB_HIRES.may_load(deps.storage, job_key(job_id)?)?.unwrap_or_default();
+        if let Some(max_agents) = job.max_agents {
+            if hires >= max_agents {
+                return Err(ContractError::MaxAgentsReached { max_agents });
+            }
+        }
+        JOB_HIRES.save(deps.storage, job_key(job_id)?, &(hires + 1))?;
+        application.status = ApplicationStatus::Accepted;
+        APPLICATIONS.save(deps.storage, (job_key(job_id)?, agent.clone()), &application)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "accept application")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("agent", agent))
+    }
+
+    pub fn reject_application(
+        deps: DepsMut,
+        info: MessageInfo,
+        job_id: Uint128,
+        agent: Addr,
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        if job.owner != info.sender {
+            return Err(ContractError::NotJobOwner {});
+        }
+        let key = (job_key(job_id)?, agent.clone());
+        let mut application = APPLICATIONS
+            .may_load(deps.storage, key.clone())?
+            .ok_or(ContractError::ApplicationNotFound {})?;
+        match application.status {
+            ApplicationStatus::Pending => {}
+            // a hired agent that never took its slot gives the place back
+            ApplicationStatus::Accepted => {
+                if JOB_AGENTS.has(deps.storage, key.clone()) {
+                    return Err(ContractError::AgentOnJob {});
+                }
+                JOB_HIRES.update(deps.storage, job_key(job_id)?, |hires| -> StdResult<_> {
+                    Ok(hires.unwrap_or_default().saturating_sub(1))
+                })?;
+            }
+            ApplicationStatus::Rejected => return Err(ContractError::ApplicationNotPending {}),
+        }
+        application.status = ApplicationStatus::Rejected;
+        APPLICATIONS.save(deps.storage, key, &application)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "reject application")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("agent", agent))
+    }
+
+    pub fn withdraw_application(
+        deps: DepsMut,
+        info: MessageInfo,
+        job_id: Uint128,
+    ) -> Result<Response, ContractError> {
+        let key = (job_key(job_id)?, info.sender.clone());
+        let application = APPLICATIONS
+            .may_load(deps.storage, key.clone())?
+            .ok_or(ContractError::ApplicationNotFound {})?;
+        // a hired agent gives its place back once it left the job
+        if application.status == ApplicationStatus::Accepted {
+            if JOB_AGENTS.has(deps.storage, key.clone()) {
+                return Err(ContractError::AgentOnJob {});
+            }
+            JOB_HIRES.update(deps.storage, job_key(job_id)?, |hires| -> StdResult<_> {
+                Ok(hires.unwrap_or_default().saturating_sub(1))
+            })?;
+        }
+        APPLICATIONS.remove(deps.storage, key);
+
+        Ok(Response::new()
+            .add_attribute("action", "withdraw application")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("agent", info.sender))
+    }
+
+    fn load_pending_application(deps: Deps, job_id: Uint128, agent: &Addr) -> Result<Application, ContractError> {
+        let application = APPLICATIONS
+            .may_load(deps.storage, (job_key(job_id)?, agent.clone()))?
+            .ok_or(ContractError::ApplicationNotFound {})?;
+        if application.status != ApplicationStatus::Pending {
+            return Err(ContractError::ApplicationNotPending {});
+        }
+        Ok(application)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_auction(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        job_id: Uint128,
+        slots: u32,
+        commit_period: u64,
+        reveal_period: u64,
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        if job.owner != info.sender {
+            return Err(ContractError::NotJobOwner {});
+        }
+        ensure_job_status(&job, &[JobStatus::Open])?;
+        ensure_no_auction(deps.storage, job_id)?;
+        if slots == 0 {
+            return Err(ContractError::InvalidAuction { reason: "slots must be non-zero".to_string() });
+        }
+        if slots > MAX_AUCTION_SLOTS {
+            return Err(ContractError::InvalidAuction { reason: format!("at most {MAX_AUCTION_SLOTS} slots") });
+        }
+        if commit_period == 0 || reveal_period == 0 {
+            return Err(ContractError::InvalidAuction { reason: "periods must be non-zero".to_string() });
+        }
+        // every slot must be fillable without going over the hiring limit
+        let hires = JOB_HIRES.may_load(deps.storage, job_key(job_id)?)?.unwrap_or_default();
+        if let Some(max_agents) = job.max_agents {
+            if hires.saturating_add(slots) > max_agents {
+                return Err(ContractError::MaxAgentsReached { max_agents });
+            }
+        }
+        let commit_deadline = env.block.time.plus_seconds(commit_period);
+        let auction = Auction {
+            slots,
+            commit_deadline,
+            reveal_deadline: commit_deadline.plus_seconds(reveal_period),
+            bids: 0,
+            settled: false,
+        };
+        AUCTIONS.save(deps.storage, job_key(job_id)?, &auction)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "open auction")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("slots", slots.to_string()))
+    }
+
+    pub fn commit_bid(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        job_id: Uint128,
+        hash: Binary,
+        deposit: Uint128,
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        let payment = collect_payment(&job.payment_asset, &info, &env.contract.address, deposit)?;
+        let response = add_bid(deps, env, info.sender, job.payment_asset, job_id, hash, deposit)?;
+
+        Ok(response.add_messages(payment))
+    }
+
+    fn add_bid(
+        deps: DepsMut,
+        env: Env,
+        sender: Addr,
+        asset: Asset,
+        job_id: Uint128,
+        hash: Binary,
+        deposit: Uint128,
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        ensure_job_status(&job, &[JobStatus::Open])?;
+        if asset != job.payment_asset {
+            return Err(ContractError::WrongAsset { expected: job.payment_asset.to_string() });
+        }
+        let mut auction = load_auction(deps.storage, job_id)?;
+        if auction.settled || env.block.time >= auction.commit_deadline {
+            return Err(ContractError::BiddingClosed {});
+        }
+        let key = (job_key(job_id)?, sender.clone());
+        if BIDS.has(deps.storage, key.clone()) {
+            return Err(ContractError::AlreadyBid {});
+        }
+        // agents already hired or waiting for the owner stay out of the auction
+        if APPLICATIONS
+            .may_load(deps.storage, key.clone())?
+            .is_some_and(|application| application.status != ApplicationStatus::Rejected)
+        {
+            return Err(ContractError::AlreadyApplied {});
+        }
+        // a deposit that could not cover any valid collateral only fills the auction
+        if deposit.is_zero() || deposit < job.min_agent_stake {
+            return Err(ContractError::InvalidBidCollateral { min_agent_stake: job.min_agent_stake, deposit });
+        }
+        let max_bids = auction.slots * MAX_BIDS_PER_SLOT;
+        if auction.bids >= max_bids {
+            return Err(ContractError::TooManyBids { max_bids });
+        }
+        let bid = Bid {
+            commitment: hash,
+            deposit,
+            committed_at: env.block.time,
+            rate: None,
+            collateral: None,
+        };
+        BIDS.save(deps.storage, key, &bid)?;
+        auction.bids += 1;
+        AUCTIONS.save(deps.storage, job_key(job_id)?, &auction)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "commit bid")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("bidder", sender)
+            .add_attribute("deposit", deposit))
+    }
+
+    pub fn reveal_bid(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        job_id: Uint128,
+        rate: Uint128,
+        collateral: Uint128,
+        salt: String,
+    ) -> Result<Response, ContractError> {
+        let auction = load_auction(deps.storage, job_id)?;
+        if auction.settled
+            || env.block.time < auction.commit_deadline
+            || env.block.time >= auction.reveal_deadline
+        {
+            return Err(ContractError::NotBidRevealPeriod {});
+        }
+        let key = (job_key(job_id)?, info.sender.clone());
+        let mut bid = BIDS
+            .may_load(deps.storage, key.clone())?
+            .ok_or(ContractError::NoBidFound {})?;
+        if bid_commitment(job_id, &info.sender, rate, collateral, &salt) != bid.commitment {
+            return Err(ContractError::InvalidBidReveal {});
+        }
+        let job = load_job(deps.as_ref(), job_id)?;
+        if collateral.is_zero() || collateral < job.min_agent_stake || collateral > bid.deposit {
+            return Err(ContractError::InvalidBidCollateral {
+                min_agent_stake: job.min_agent_stake,
+                deposit: bid.deposit,
+            });
+        }
+        bid.rate = Some(rate);
+        bid.collateral = Some(collateral);
+        BIDS.save(deps.storage, key, &bid)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "reveal bid")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("bidder", info.sender)
+            .add_attribute("rate", rate))
+    }
+
+    // the lowest rates win, ties go to the larger collateral then to the earlier commitment.
+    // Nobody wins once the job left Open, every deposit is credited back
+    pub fn settle_auction(
+        deps: DepsMut,
+        env: Env,
+        job_id: Uint128,
+    ) -> Result<Response, ContractError> {
+        let mut auction = load_auction(deps.storage, job_id)?;
+        if auction.settled {
+            return Err(ContractError::AuctionSettled {});
+        }
+        if env.block.time < auction.reveal_deadline {
+            return Err(ContractError::AuctionNotFinished {});
+        }
+        let job = load_job(deps.as_ref(), job_id)?;
+        let bids = BIDS
+            .prefix(job_key(job_id)?)
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        BIDS.prefix(job_key(job_id)?).clear(deps.storage, None);
+
+        let mut revealed = bids
+            .iter()
+            .filter_map(|(bidder, bid)| Some((bidder, bid, bid.rate?, bid.collateral?)))
+            .collect::<Vec<_>>();
+        revealed.sort_by(|(_, a, a_rate, a_collateral), (_, b, b_rate, b_collateral)| {
+            a_rate
+                .cmp(b_rate)
+                .then(b_collateral.cmp(a_collateral))
+                .then(a.committed_at.cmp(&b.committed_at))
+        });
+        let winners = if job.status == JobStatus::Open {
+            revealed.into_iter().take(auction.slots as usize).collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
+
+        let mut response = Response::new()
+            .add_attribute("action", "settle auction")
+            .add_attribute("job_id", job_id.to_string());
+        for (bidder, bid, rate, collateral) in &winners {
+            let agent_stake = AssetAmount { asset: job.payment_asset.clone(), amount: *collateral };
+            AGENT_STAKE.save(deps.storage, ((*bidder).clone(), job_key(job_id)?), &agent_stake)?;
+            JOB_AGENTS.save(deps.storage, (job_key(job_id)?, (*bidder).clone()), &AgentSlot { cost_per_unit_time: *rate })?;
+            let application = Application {
+                proposed_rate: *rate,
+                proposal_uri: String::new(),
+                applied_at: bid.committed_at,
+                status: ApplicationStatus::Accepted,
+            };
+            APPLICATIONS.save(deps.storage, (job_key(job_id)?, (*bidder).clone()), &application)?;
+            credit_claimable(deps.storage, bidder, &job.payment_asset, bid.deposit - *collateral)?;
+            response = response.add_attribute("winner", bidder.to_string());
+        }
+        JOB_HIRES.update(deps.storage, job_key(job_id)?, |hires| -> StdResult<_> {
+            Ok(hires.unwrap_or_default() + winners.len() as u32)
+        })?;
+        // losers get their whole deposit back, unrevealed bids forfeit it to the job owner
+        // so a bidder can not commit and only reveal if the others bid high
+        let mut forfeited = Uint128::zero();
+        for (bidder, bid) in &bids {
+            if bid.rate.is_none() {
+                forfeited += bid.deposit;
+            } else if !winners.iter().any(|(winner, ..)| *winner == bidder) {
+                credit_claimable(deps.storage, bidder, &job.payment_asset, bid.deposit)?;
+            }
+        }
+        credit_claimable(deps.storage, &job.owner, &job.payment_asset, forfeited)?;
+        response = response.add_attribute("forfeited", forfeited);
+        auction.settled = true;
+        AUCTIONS.save(deps.storage, job_key(job_id)?, &auction)?;
+
+        Ok(response)
+    }
+
+    pub fn distribute_rewards_by_agent(
+        deps: DepsMut,
+        info: MessageInfo,
+        job_id: Uint128
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        ensure_payout_authority(&job, &info.sender)?;
+        ensure_job_status(&job, &[JobStatus::Completed])?;
+
+        let rewards_owner_stake = load_stake(deps.storage, &USER_STAKE, &job.owner, &job)?;
+        // the owner stake is drained by the first distribution, so this also
+        // stops the same job from being paid out twice
+        if rewards_owner_stake.amount.is_zero() {
+            return Err(ContractError::NothingToDistribute {});
+        }
+        release_escrow_to_agents(deps, &job)?;
+        Ok(Response::new()
+            .add_attribute("action", "distribution rewards by agent"))
+    }
+
+    // split the owner stake minus the protocol fee equally between the agents and repay their own stake,
+    // rewards are credited in the job's payment asset and the stake in its own asset, the agents leave the job
+    fn release_escrow_to_agents(deps: DepsMut, job: &Job) -> Result<(), ContractError> {
+        let job_id = job.id;
+        let mut rewards_owner_stake = load_stake(deps.storage, &USER_STAKE, &job.owner, job)?;
+        let job_agent_addrs = job_agents(deps.storage, job_id)?;
+        if job_agent_addrs.is_empty() {
+            return Err(ContractError::NoAgents {});
+        }
+        let config = CONFIG.load(deps.storage)?;
+        let protocol_fee = take_protocol_fee(deps.storage, &config, &rewards_owner_stake.asset, rewards_owner_stake.amount)?;
+        let rewards_per_agent =
+            (rewards_owner_stake.amount - protocol_fee) / Uint128::from(job_agent_addrs.len() as u128);
+        rewards_owner_stake.amount = Uint128::zero();
+        USER_STAKE.save(
+            deps.storage,
+            (job.owner.clone(), job_key(job_id)?),
+            &rewards_owner_stake,
+        )?;
+        for (agent_addr, _) in job_agent_addrs {
+            // repay staked amount for agent
+            let mut agent_stake = load_stake(deps.storage, &AGENT_STAKE, &agent_addr, job)?;
+            credit_claimable(deps.storage, &agent_addr, &agent_stake.asset, agent_stake.amount)?;
+            agent_stake.amount = Uint128::zero();
+            AGENT_STAKE.save(deps.storage, (agent_addr.clone(), job_key(job_id)?), &agent_stake)?;
+            JOB_AGENTS.remove(deps.storage, (job_key(job_id)?, agent_addr.clone()));
+
+            // credit rewards to agent
+            credit_claimable(deps.storage, &agent_addr, &rewards_owner_stake.asset, rewards_per_agent)?;
+        }
+        JOB_HIRES.remove(deps.storage, job_key(job_id)?);
+        Ok(())
+    }
+
+    pub fn distribute_rewards_by_time(
+        deps: DepsMut,
+        info: MessageInfo,
+        job_id: Uint128
+    ) -> Result<Response, ContractError> {
+        let job = load_job(deps.as_ref(), job_id)?;
+        ensure_payout_authority(&job, &info.sender)?;
+        ensure_job_status(&job, &[JobStatus::Completed])?;
+
+        let mut rewards_owner_stake = load_stake(deps.storage, &USER_STAKE, &job.owner, &job)?;
+        let job_agent_addrs = job_agents(deps.storage, job_id)?;
+        if job_agent_addrs.is_empty() {
+            return Err(ContractError::NoAgents {});
+        }
+        if !query::check_if_enough_rewards(
+            deps.as_ref(),
+            job_id
+        )? {
+            return Err(ContractError::InsufficientStake {});
+        }
+        let config = CONFIG.load(deps.storage)?;
+        let mut total_cost_per_unit_time = Uint128::zero();
+        for (agent_addr, slot) in job_agent_addrs {
+            let agent_cost = slot.cost_per_unit_time;
+            total_cost_per_unit_time += agent_cost;
+
+            // credit rewards to agent
+            let protocol_fee = take_protocol_fee(deps.storage, &config, &job.payment_asset, agent_cost)?;
+            credit_claimable(deps.storage, &agent_addr, &job.payment_asset, agent_cost - protocol_fee)?;
+        }
+        rewards_owner_stake.amount -= total_cost_per_unit_time;
+        USER_STAKE.save(
+            deps.storage,
+            (job.owner.clone(), job_key(job_id)?),
+            &rewards_owner_stake,
+        )?;
+        Ok(Response::new()
+            .add_attribute("action", "distribution rewards by time unit"))
+    }
+
+    pub fn commit_vote(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        job_id: Uint128,
+        hash: Binary,
+    ) -> Result<Response, ContractError> {
+        let mut dispute = DISPUTES
+            .may_load(deps.storage, job_key(job_id)?)?
+            .ok_or(ContractError::DisputeNotFound { job_id })?;
+        if dispute.outcome.is_some() || env.block.time >= dispute.commit_deadline {
+            return Err(ContractError::VotingClosed {});
+        }
+        // parties of the job are never drawn, so they cannot judge their own dispute
+        if !dispute.panel.contains(&info.sender) {
+            return Err(ContractError::NotPanelMember {});
+        }
+        // the bond may have been released since the panel was drawn
+        let dispute_config = DISPUTE_CONFIG.load(deps.storage)?;
+        let juror = JURORS
+            .may_load(deps.storage, info.sender.clone())?
+            .filter(|juror| juror.bonded >= dispute_config.min_juror_bond)
+            .ok_or(ContractError::NotJuror {})?;
+        let key = (job_key(job_id)?, dispute.round, info.sender.clone());
+        if VOTE_COMMITS.has(deps.storage, key.clone()) {
+            return Err(ContractError::AlreadyVoted {});
+        }
+
+        let weight = dispute_config.vote_weight(juror.bonded);
+        VOTE_COMMITS.save(deps.storage, key, &VoteCommit { commitment: hash, weight })?;
+        dispute.commits += Uint128::one();
+        DISPUTES.save(deps.storage, job_key(job_id)?, &dispute)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "commit vote")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("juror", info.sender))
+    }
+
+    pub fn reveal_vote(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        job_id: Uint128,
+        is_accept: bool,
+        salt: String,
+    ) -> Result<Response, ContractError> {
+        let mut dispute = DISPUTES
+            .may_load(deps.storage, job_key(job_id)?)?
+            .ok_or(ContractError::DisputeNotFound { job_id })?;
+        if dispute.outcome.is_some()
+            || env.block.time < dispute.commit_deadline
+            || env.block.time >= dispute.reveal_deadline
+        {
+            return Err(ContractError::NotRevealPeriod {});
+        }
+        let key = (job_key(job_id)?, dispute.round, info.sender.clone());
+        let commit = VOTE_COMMITS
+            .may_load(deps.storage, key.clone())?
+            .ok_or(ContractError::NoCommitFound {})?;
+        if vote_commitment(job_id, dispute.round, &info.sender, is_accept, &salt) != commit.commitment {
+            return Err(ContractError::InvalidReveal {});
+        }
+
+        VOTE_COMMITS.remove(deps.storage, key.clone());
+        let weight = commit.weight;
+        DISPUTE_VOTES.save(deps.storage, key, &Vote { is_accept, weight })?;
+
+        if is_accept {
+            dispute.accept_votes += Uint128::one();
+            dispute.accept_weight += weight;
+        } else {
+            dispute.reject_votes += Uint128::one();
+            dispute.reject_weight += weight;
+        }
+        DISPUTES.save(deps.storage, job_key(job_id)?, &dispute)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "reveal vote")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("juror", info.sender)
+            .add_attribute("is_accept", is_accept.to_string())
+            .add_attribute("weight", weight))
+    }
+
+    pub fn finalize_dispute(
+        mut deps: DepsMut,
+        env: Env,
+        job_id: Uint128,
+    ) -> Result<Response, ContractError> {
+        let mut dispute = DISPUTES
+            .may_load(deps.storage, job_key(job_id)?)?
+            .ok_or(ContractError::DisputeNotFound { job_id })?;
+        if dispute.outcome.is_some() {
+            return Err(ContractError::DisputeFinalized {});
+        }
+        let dispute_config = DISPUTE_CONFIG.load(deps.storage)?;
+        let mut response = Response::new()
+            .add_attribute("action", "finalize dispute")
+            .add_attribute("job_id", job_id.to_string());
+
+        // the round is already tallied and nobody appealed it in time
+        if let Some(appeal_deadline) = dispute.appeal_deadline {
+            if env.block.time < appeal_deadline {
+                return Err(ContractError::AppealPeriodNotFinished {});
+            }
+            let event = settle_dispute(deps.branch(), &mut dispute, &dispute_config)?;
+            DISPUTES.save(deps.storage, job_key(job_id)?, &dispute)?;
+            return Ok(response.add_event(event));
+        }
+
+        // a side that missed its step of the draw loses: the first party when it did not reveal
+        // once the other side committed, the other side otherwise
+        if let Some(draw) = &dispute.draw {
+            if env.block.time < draw.deadline {
+                return Err(ContractError::VotingNotFinished {});
+            }
+            let job = load_job(deps.as_ref(), job_id)?;
+            let first_party_failed = draw.second_hash.is_some() && draw.first_seed.is_none();
+            let owner_failed = first_party_failed == (draw.first_party == job.owner);
+            let outcome = if owner_failed { DisputeOutcome::Accepted } else { DisputeOutcome::Rejected };
+            // on an appeal round the appellant committed first, its bond goes to the jurors of the
+            // appealed round when it missed its step and back to it otherwise
+            if let Some(appealed_round) = dispute.rounds.last().cloned() {
+                if let Some(appellant) = &appealed_round.appellant {
+                    let (coherent_jurors, _) = split_jurors(
+                        deps.as_ref(),
+                        job_id,
+                        dispute.round - 1,
+                        &appealed_round.panel,
+                        &appealed_round.outcome,
+                    )?;
+                    let coherent_weight: Uint128 = coherent_jurors.iter().map(|(_, weight)| *weight).sum();
+                    let bond_asset = BOND_ASSET.load(deps.storage)?;
+                    if first_party_failed && !coherent_weight.is_zero() {
+                        reward_jurors(deps.branch(), &coherent_jurors, coherent_weight, &bond_asset, appealed_round.appeal_bond)?;
+                    } else {
+                        credit_claimable(deps.storage, appellant, &bond_asset, appealed_round.appeal_bond)?;
+                    }
+                }
+            }
+            dispute.rounds.push(DisputeRound::without_panel(outcome));
+            let event = settle_dispute(deps.branch(), &mut dispute, &dispute_config)?;
+            DISPUTES.save(deps.storage, job_key(job_id)?, &dispute)?;
+            return Ok(response
+                .add_attribute("draw_failed_by", if owner_failed { "owner" } else { "agents" })
+                .add_event(event));
+        }
+
+        if env.block.time < dispute.reveal_deadline {
+            return Err(ContractError::VotingNotFinished {});
+        }
+        let (event, outcome) = tally_round(deps.branch(), &mut dispute, &dispute_config)?;
+        response = response.add_event(event);
+
+        // only a decided outcome has a losing side to appeal it
+        let appealable = matches!(outcome, DisputeOutcome::Accepted | DisputeOutcome::Rejected)
+            && dispute.round < dispute_config.max_appeals;
+        if appealable {
+            let appeal_deadline = env.block.time.plus_seconds(dispute_config.appeal_period);
+            dispute.appeal_deadline = Some(appeal_deadline);
+            DISPUTES.save(deps.storage, job_key(job_id)?, &dispute)?;
+            return Ok(response.add_attribute("appeal_deadline", appeal_deadline.seconds().to_string()));
+        }
+        let event = settle_dispute(deps.branch(), &mut dispute, &dispute_config)?;
+        DISPUTES.save(deps.storage, job_key(job_id)?, &dispute)?;
+        Ok(response.add_event(event))
+    }
+
+    // decide the current round and settle its jurors, the escrow is not touched
+    fn tally_round(
+        mut deps: DepsMut,
+        dispute: &mut Dispute,
+        dispute_config: &DisputeConfig,
+    ) -> Result<(Event, DisputeOutcome), ContractError> {
+        let revealed_votes = dispute.accept_votes + dispute.reject_votes;
+        let outcome = if revealed_votes < Uint128::from(dispute_config.quorum) {
+            DisputeOutcome::NoQuorum
+        } else if dispute.accept_weight > dispute.reject_weight {
+            DisputeOutcome::Accepted
+        } else if dispute.accept_weight < dispute.reject_weight {
+            DisputeOutcome::Rejected
+        } else {
+            DisputeOutcome::Tied
+        };
+
+        // incoherent jurors are slashed even when nobody revealed, what they lose waits in the
+        // juror pool until a round has coherent jurors to reward
+        let (coherent_jurors, incoherent_jurors) =
+            split_jurors(deps.as_ref(), dispute.job_id, dispute.round, &dispute.panel, &outcome)?;
+        let coherent_weight: Uint128 = coherent_jurors.iter().map(|(_, weight)| *weight).sum();
+        let juror_slashed = slash_jurors(deps.branch(), &incoherent_jurors, dispute_config.juror_slash_bps)?;
+        let juror_pool = JUROR_POOL.may_load(deps.storage)?.unwrap_or_default() + juror_slashed;
+        let mut reward_pool = if coherent_weight.is_zero() {
+            JUROR_POOL.save(deps.storage, &juror_pool)?;
+            Uint128::zero()
+        } else {
+            JUROR_POOL.remove(deps.storage);
+            juror_pool
+        };
+
+        // the bond of the appeal that opened this round goes back to the appellant when the
+        // appealed outcome is overturned, and to the jurors who confirmed it otherwise
+        let bond_asset = BOND_ASSET.load(deps.storage)?;
+        if let Some(appealed_round) = dispute.rounds.last() {
+            if let Some(appellant) = &appealed_round.appellant {
+                if appealed_round.outcome == outcome && !coherent_weight.is_zero() {
+                    reward_pool += appealed_round.appeal_bond;
+                } else {
+                    credit_claimable(deps.storage, appellant, &bond_asset, appealed_round.appeal_bond)?;
+                }
+            }
+        }
+        reward_jurors(deps.branch(), &coherent_jurors, coherent_weight, &bond_asset, reward_pool)?;
+
+        dispute.rounds.push(DisputeRound {
+            panel: dispute.panel.clone(),
+            accept_votes: dispute.accept_votes,
+            reject_votes: dispute.reject_votes,
+            accept_weight: dispute.accept_weight,
+            reject_weight: dispute.reject_weight,
+            outcome: outcome.clone(),
+            appellant: None,
+            appeal_bond: Uint128::zero(),
+        });
+
+        let event = Event::new("dispute_round")
+            .add_attribute("job_id", dispute.job_id.to_string())
+            .add_attribute("round", dispute.round.to_string())
+            .add_attribute("outcome", outcome.to_string())
+            .add_attribute("accept_weight", dispute.accept_weight)
+            .add_attribute("reject_weight", dispute.reject_weight)
+            .add_attribute("juror_slashed", juror_slashed);
+        Ok((event, outcome))
+    }
+
+    // release the escrow as decided by the last round, its coherent jurors share the dispute fee
+    fn settle_dispute(
+        mut deps: DepsMut,
+        dispute: &mut Dispute,
+        dispute_config: &DisputeConfig,
+    ) -> Result<Event, ContractError> {
+        let job_id = dispute.job_id;
+        let last_round = dispute
+            .rounds
+            .last()
+            .cloned()
+            .ok_or(ContractError::VotingNotFinished {})?;
+        let outcome = last_round.outcome;
+        let (coherent_jurors, _) =
+            split_jurors(deps.as_ref(), job_id, dispute.round, &last_round.panel, &outcome)?;
+        let coherent_weight: Uint128 = coherent_jurors.iter().map(|(_, weight)| *weight).sum();
+        let dispute_fee_bps = if coherent_weight.is_zero() { 0 } else { dispute_config.dispute_fee_bps };
+
+        let mut job = load_job(deps.as_ref(), job_id)?;
+        let dispute_fees = match outcome {
+            DisputeOutcome::Accepted => {
+                job.status = JobStatus::Completed;
+                // the owner lost, the fee comes out of the escrow before it is split
+                let mut owner_stake = load_stake(deps.storage, &USER_STAKE, &job.owner, &job)?;
+                let dispute_fee = owner_stake.amount.multiply_ratio(dispute_fee_bps, 10_000u128);
+                owner_stake.amount -= dispute_fee;
+                USER_STAKE.save(deps.storage, (job.owner.clone(), job_key(job_id)?), &owner_stake)?;
+                release_escrow_to_agents(deps.branch(), &job)?;
+                vec![(owner_stake.asset, dispute_fee)]
+            }
+            DisputeOutcome::Rejected => {
+                job.status = JobStatus::Cancelled;
+                refund_escrow(deps.branch(), &job, dispute_config.agent_slash_bps, dispute_fee_bps)?
+            }
+            // no decision: everybody gets their stake back and nobody is slashed
+            DisputeOutcome::Tied | DisputeOutcome::NoQuorum => {
+                job.status = JobStatus::Cancelled;
+                refund_escrow(deps.branch(), &job, 0, 0)?
+            }
+        };
+        JOBS.save(deps.storage, job_key(job_id)?, &job)?;
+        for (asset, dispute_fee) in &dispute_fees {
+            reward_jurors(deps.branch(), &coherent_jurors, coherent_weight, asset, *dispute_fee)?;
+        }
+        dispute.draw = None;
+        dispute.appeal_deadline = None;
+        dispute.outcome = Some(outcome.clone());
+        OPEN_DISPUTES.remove(deps.storage, job_key(job_id)?);
+
+        let dispute_fee = dispute_fees
+            .iter()
+            .map(|(asset, amount)| format!("{amount} {asset}"))
+            .collect::<Vec<_>>()
+            .join(",");
+        let event = Event::new("dispute_outcome")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("outcome", outcome.to_string())
+            .add_attribute("rounds", dispute.rounds.len().to_string())
+            .add_attribute("dispute_fee", dispute_fee);
+        Ok(event)
+    }
+
+    pub fn appeal(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        job_id: Uint128,
+        seed_hash: Binary,
+    ) -> Result<Response, ContractError> {
+        let mut dispute = DISPUTES
+            .may_load(deps.storage, job_key(job_id)?)?
+            .ok_or(ContractError::DisputeNotFound { job_id })?;
+        if dispute.outcome.is_some() || dispute.appeal_deadline.is_none_or(|deadline| env.block.time >= deadline) {
+            return Err(ContractError::NotAppealable {});
+        }
+        let job = load_job(deps.as_ref(), job_id)?;
+        let Some(appealed_round) = dispute.rounds.last_mut() else {
+            return Err(ContractError::NotAppealable {});
+        };
+        // only the side the round went against may appeal
+        let is_losing_side = match appealed_round.outcome {
+            DisputeOutcome::Accepted => job.owner == info.sender,
+            DisputeOutcome::Rejected => is_job_agent(deps.as_ref(), job_id, &info.sender)?,
+            DisputeOutcome::Tied | DisputeOutcome::NoQuorum => false,
+        };
+        if !is_losing_side {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let dispute_config = DISPUTE_CONFIG.load(deps.storage)?;
+        let appeal_bond = dispute_config.appeal_bond_for(dispute.round);
+        appealed_round.appellant = Some(info.sender.clone());
+        appealed_round.appeal_bond = appeal_bond;
+
+        // the panel of the new round is drawn like the first one, the appellant commits first
+        if eligible_jurors(deps.as_ref(), &job)?.is_empty() {
+            return Err(ContractError::NoEligibleJurors {});
+        }
+        let commit_deadline = env.block.time.plus_seconds(dispute_config.commit_period);
+        dispute.draw = Some(PanelDraw {
+            first_party: info.sender.clone(),
+            first_hash: seed_hash,
+            second_hash: None,
+            first_seed: None,
+            second_seed: None,
+            beacon: None,
+            deadline: commit_deadline,
+        });
+        dispute.round += 1;
+        dispute.panel = vec![];
+        dispute.commits = Uint128::zero();
+        dispute.accept_votes = Uint128::zero();
+        dispute.reject_votes = Uint128::zero();
+        dispute.accept_weight = Uint128::zero();
+        dispute.reject_weight = Uint128::zero();
+        dispute.commit_deadline = commit_deadline;
+        dispute.reveal_deadline = commit_deadline.plus_seconds(dispute_config.reveal_period);
+        dispute.appeal_deadline = None;
+        DISPUTES.save(deps.storage, job_key(job_id)?, &dispute)?;
+
+        let bond_asset = BOND_ASSET.load(deps.storage)?;
+        let payment = collect_payment(&bond_asset, &info, &env.contract.address, appeal_bond)?;
+        Ok(Response::new()
+            .add_attribute("action", "appeal")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("appellant", info.sender)
+            .add_attribute("round", dispute.round.to_string())
+            .add_attribute("appeal_bond", appeal_bond)
+            .add_attribute("draw_deadline", commit_deadline.seconds().to_string())
+            .add_messages(payment))
+    }
+
+    type AssetTotals = Vec<(Asset, Uint128)>;
+
+    // give the owner stake back and repay the agents, minus `agent_slash_bps`
+    // of their stake which goes to the owner and `dispute_fee_bps` kept for the jurors,
+    // slashes and fees stay in the asset of each agent's collateral, the agents leave the job
+    fn refund_escrow(
+        deps: DepsMut,
+        job: &Job,
+        agent_slash_bps: u16,
+        dispute_fee_bps: u16,
+    ) -> Result<AssetTotals, ContractError> {
+        let job_id = job.id;
+        let mut dispute_fees: AssetTotals = vec![];
+        let mut owner_stake = load_stake(deps.storage, &USER_STAKE, &job.owner, job)?;
+        credit_claimable(deps.storage, &job.owner, &owner_stake.asset, owner_stake.amount)?;
+        owner_stake.amount = Uint128::zero();
+        USER_STAKE.save(deps.storage, (job.owner.clone(), job_key(job_id)?), &owner_stake)?;
+
+        for (agent_addr, _) in job_agents(deps.storage, job_id)? {
+            let mut agent_stake = load_stake(deps.storage, &AGENT_STAKE, &agent_addr, job)?;
+            let agent_stake_amount = agent_stake.amount;
+            agent_stake.amount = Uint128::zero();
+            AGENT_STAKE.save(deps.storage, (agent_addr.clone(), job_key(job_id)?), &agent_stake)?;
+            JOB_AGENTS.remove(deps.storage, (job_key(job_id)?, agent_addr.clone()));
+            let slash_amount = agent_stake_amount.multiply_ratio(agent_slash_bps, 10_000u128);
+            let fee_amount = agent_stake_amount.multiply_ratio(dispute_fee_bps, 10_000u128);
+            add_to_asset(&mut dispute_fees, &agent_stake.asset, fee_amount);
+            let repay_amount = agent_stake_amount - slash_amount - fee_amount;
+            credit_claimable(deps.storage, &job.owner, &agent_stake.asset, slash_amount)?;
+            credit_claimable(deps.storage, &agent_addr, &agent_stake.asset, repay_amount)?;
+        }
+        JOB_HIRES.remove(deps.storage, job_key(job_id)?);
+        Ok(dispute_fees)
+    }
+
+    fn add_to_asset(totals: &mut AssetTotals, asset: &Asset, amount: Uint128) {
+        match totals.iter_mut().find(|(total_asset, _)| total_asset == asset) {
+            Some((_, total)) => *total += amount,
+            None => totals.push((asset.clone(), amount)),
+        }
+    }
+
+    type JurorSplit = (Vec<(Addr, Uint128)>, Vec<Addr>);
+
+    // coherent jurors voted with the outcome, or revealed at all when there is no winner,
+    // incoherent ones voted against it or were drawn and never revealed a vote
+    fn split_jurors(
+        deps: Deps,
+        job_id: Uint128,
+        round: u32,
+        panel: &[Addr],
+        outcome: &DisputeOutcome,
+    ) -> StdResult<JurorSplit> {
+        let mut coherent_jurors = vec![];
+        let mut incoherent_jurors = vec![];
+        for juror in panel {
+            let Some(vote) = DISPUTE_VOTES.may_load(deps.storage, (job_key(job_id)?, round, juror.clone()))? else {
+                incoherent_jurors.push(juror.clone());
+                continue;
+            };
+            let is_coherent = match outcome {
+                DisputeOutcome::Accepted => vote.is_accept,
+                DisputeOutcome::Rejected => !vote.is_accept,
+                DisputeOutcome::Tied | DisputeOutcome::NoQuorum => true,
+            };
+            if is_coherent {
+                coherent_jurors.push((juror.clone(), vote.weight));
+            } else {
+                incoherent_jurors.push(juror.clone());
+            }
+        }
+        Ok((coherent_jurors, incoherent_jurors))
+    }
+
+    // slash bonded stake first, then stake that is still unbonding
+    fn slash_jurors(deps: DepsMut, jurors: &[Addr], juror_slash_bps: u16) -> StdResult<Uint128> {
+        let mut total_slashed = Uint128::zero();
+        for juror_addr in jurors {
+            let Some(mut juror) = JURORS.may_load(deps.storage, juror_addr.clone())? else {
+                continue;
+            };
+            let slash_amount = (juror.bonded + juror.unbonding).multiply_ratio(juror_slash_bps, 10_000u128);
+            let from_bonded = slash_amount.min(juror.bonded);
+            juror.bonded -= from_bonded;
+            juror.unbonding -= slash_amount - from_bonded;
+            if juror.bonded.is_zero() && juror.unbonding.is_zero() {
+                remove_juror(deps.storage, juror_addr)?;
+            } else {
+                JURORS.save(deps.storage, juror_addr.clone(), &juror)?;
+            }
+            total_slashed += slash_amount;
+        }
+        Ok(total_slashed)
+    }
+
+    // split `amount` pro rata to the vote weights, rounding dust goes to the last juror
+    fn reward_jurors(
+        deps: DepsMut,
+        jurors: &[(Addr, Uint128)],
+        total_weight: Uint128,
+        asset: &Asset,
+        amount: Uint128,
+    ) -> StdResult<()> {
+        if amount.is_zero() || total_weight.is_zero() {
+            return Ok(());
+        }
+        let mut remaining = amount;
+        for (index, (juror_addr, weight)) in jurors.iter().enumerate() {
+            let reward = if index == jurors.len() - 1 {
+                remaining
+            } else {
+                amount.multiply_ratio(*weight, total_weight)
+            };
+            remaining -= reward;
+            credit_claimable(deps.storage, juror_addr, asset, reward)?;
+        }
+        Ok(())
+    }
+
+    pub fn withdraw(
+        deps: DepsMut,
+        info: MessageInfo,
+        asset: Asset,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        let key = (info.sender.clone(), asset.to_string());
+        let mut claimable = CLAIMABLE
+            .may_load(deps.storage, key.clone())?
+            .ok_or(ContractError::NothingToWithdraw {})?;
+        if amount.is_zero() {
+            return Err(ContractError::NothingToWithdraw {});
+        }
+        if claimable.amount < amount {
+            return Err(ContractError::InsufficientClaimable { available: claimable.amount });
+        }
+        claimable.amount -= amount;
+        if claimable.amount.is_zero() {
+            CLAIMABLE.remove(deps.storage, key);
+        } else {
+            CLAIMABLE.save(deps.storage, key, &claimable)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "withdraw")
+            .add_attribute("recipient", info.sender.clone())
+            .add_attribute("asset", asset.to_string())
+            .add_attribute("amount", amount)
+            .add_message(transfer_msg(&asset, &info.sender, amount)?))
+    }
+
+    pub fn register_juror(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        let bond_asset = BOND_ASSET.load(deps.storage)?;
+        let payment = collect_payment(&bond_asset, &info, &env.contract.address, amount)?;
+        let response = bond_juror(deps, env, info.sender.clone(), amount)?;
+
+        Ok(response.add_messages(payment))
+    }
+
+    fn bond_juror(
+        deps: DepsMut,
+        env: Env,
+        sender: Addr,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        let dispute_config = DISPUTE_CONFIG.load(deps.storage)?;
+        let mut juror = match JURORS.may_load(deps.storage, sender.clone())? {
+            Some(juror) => juror,
+            None => {
+                let juror_count = JUROR_COUNT.may_load(deps.storage)?.unwrap_or_default();
+                if juror_count >= dispute_config.max_jurors {
+                    return Err(ContractError::JurorRegistryFull { max_jurors: dispute_config.max_jurors });
+                }
+                JUROR_COUNT.save(deps.storage, &(juror_count + 1))?;
+                Juror {
+                    bonded: Uint128::zero(),
+                    unbonding: Uint128::zero(),
+                    unbonding_release_at: None,
+                    registered_at: env.block.time,
+                }
+            }
+        };
+        juror.bonded += amount;
+        if juror.bonded < dispute_config.min_juror_bond {
+            return Err(ContractError::JurorBondTooLow {
+                min_bond: dispute_config.min_juror_bond,
+            });
+        }
+        JURORS.save(deps.storage, sender.clone(), &juror)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "register juror")
+            .add_attribute("juror", sender)
+            .add_attribute("bonded", juror.bonded))
+    }
+
+    pub fn unregister_juror(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let dispute_config = DISPUTE_CONFIG.load(deps.storage)?;
+        let mut juror = JURORS
+            .may_load(deps.storage, info.sender.clone())?
+            .ok_or(ContractError::NotJuror {})?;
+        if juror.bonded.is_zero() {
+            return Err(ContractError::NotJuror {});
+        }
+
+        // the whole bond starts unbonding, any earlier unbonding restarts its clock
+        let release_at = env.block.time.plus_seconds(dispute_config.juror_unbonding_period);
+        juror.unbonding += juror.bonded;
+        juror.bonded = Uint128::zero();
+        juror.unbonding_release_at = Some(release_at);
+        JURORS.save(deps.storage, info.sender.clone(), &juror)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "unregister juror")
+            .add_attribute("juror", info.sender)
+            .add_attribute("unbonding", juror.unbonding)
+            .add_attribute("release_at", release_at.seconds().to_string()))
+    }
+
+    pub fn withdraw_juror_bond(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let mut juror = JURORS
+            .may_load(deps.storage, info.sender.clone())?
+            .ok_or(ContractError::NotJuror {})?;
+        if juror.unbonding.is_zero() {
+            return Err(ContractError::NothingToWithdraw {});
+        }
+        if juror.unbonding_release_at.is_some_and(|release_at| env.block.time < release_at) {
+            return Err(ContractError::UnbondingNotFinished {});
+        }
+
+        let amount = juror.unbonding;
+        juror.unbonding = Uint128::zero();
+        juror.unbonding_release_at = None;
+        if juror.bonded.is_zero() {
+            remove_juror(deps.storage, &info.sender)?;
+        } else {
+            JURORS.save(deps.storage, info.sender.clone(), &juror)?;
+        }
+
+        let bond_asset = BOND_ASSET.load(deps.storage)?;
+        let msg = transfer_msg(&bond_asset, &info.sender, amount)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "withdraw juror bond")
+            .add_attribute("juror", info.sender)
+            .add_attribute("amount", amount)
+            .add_message(msg))
+    }
+
+    pub fn update_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        treasury: Option<Addr>,
+        protocol_fee_bps: Option<u16>,
+        arbiter_max_escrow: Option<AssetAmount>,
+    ) -> Result<Response, ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        if treasury.is_some() || protocol_fee_bps.is_some() {
+            ensure_role(deps.as_ref(), Role::FeeManager, &info.sender)?;
+        }
+        if arbiter_max_escrow.is_some() {
+            ensure_role(deps.as_ref(), Role::Admin, &info.sender)?;
+        }
+        if let Some(treasury) = treasury {
+            config.treasury = treasury;
+        }
+        if let Some(protocol_fee_bps) = protocol_fee_bps {
+            config.protocol_fee_bps = protocol_fee_bps;
+        }
+        validate_config(deps.api, &config)?;
+        CONFIG.save(deps.storage, &config)?;
+
+        let mut response = Response::new()
+            .add_attribute("action", "update config")
+            .add_attribute("treasury", config.treasury)
+            .add_attribute("protocol_fee_bps", config.protocol_fee_bps.to_string());
+        if let Some(limit) = arbiter_max_escrow {
+            response = response
+                .add_attribute("arbiter_max_escrow_asset", limit.asset.to_string())
+                .add_attribute("arbiter_max_escrow", limit.amount);
+            set_arbiter_max_escrow(deps.storage, limit)?;
+        }
+        Ok(response)
+    }
+
+    pub fn propose_new_admin(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        addr: Addr,
+        expiry: Option<Expiration>,
+    ) -> Result<Response, ContractError> {
+        let mut ownership = OWNERSHIP.load(deps.storage)?;
+        ensure_owner(&ownership, &info.sender)?;
+        if expiry.is_some_and(|expiry| expiry.is_expired(&env.block)) {
+            return Err(ContractError::AdminTransferExpired {});
+        }
+        let addr = deps.api.addr_validate(addr.as_str())?;
+        ownership.pending_owner = Some(addr.clone());
+        ownership.pending_expiry = expiry;
+        OWNERSHIP.save(deps.storage, &ownership)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "propose new admin")
+            .add_attribute("pending_owner", addr)
+            .add_attribute("pending_expiry", expiry.map_or("none".to_string(), |expiry| expiry.to_string())))
+    }
+
+    pub fn accept_admin(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let mut ownership = OWNERSHIP.load(deps.storage)?;
+        let Some(pending_owner) = ownership.pending_owner.take() else {
+            return Err(ContractError::NoPendingAdmin {});
+        };
+        if pending_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        if ownership.pending_expiry.take().is_some_and(|expiry| expiry.is_expired(&env.block)) {
+            return Err(ContractError::AdminTransferExpired {});
+        }
+        ownership.owner = Some(pending_owner);
+        OWNERSHIP.save(deps.storage, &ownership)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "accept admin")
+            .add_attribute("owner", info.sender))
+    }
+
+    pub fn renounce_admin(
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let ownership = OWNERSHIP.load(deps.storage)?;
+        ensure_owner(&ownership, &info.sender)?;
+        OWNERSHIP.save(deps.storage, &Ownership::default())?;
+
+        Ok(Response::new()
+            .add_attribute("action", "renounce admin")
+            .add_attribute("previous_owner", info.sender))
+    }
+
+    fn ensure_owner(ownership: &Ownership, sender: &Addr) -> Result<(), ContractError> {
+        if ownership.owner.as_ref() != Some(sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(())
+    }
+
+    pub fn update_dispute_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        dispute_config: DisputeConfig,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.as_ref(), Role::JurorManager, &info.sender)?;
+        validate_dispute_config(deps.api, &dispute_config)?;
+        DISPUTE_CONFIG.save(deps.storage, &dispute_config)?;
+
+        Ok(Response::new().add_attribute("action", "update dispute config"))
+    }
+
+    pub fn grant_role(
+        deps: DepsMut,
+        info: MessageInfo,
+        role: Role,
+        addr: Addr,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.as_ref(), Role::Admin, &info.sender)?;
+        let addr = deps.api.addr_validate(addr.as_str())?;
+        ROLES.save(deps.storage, (role.to_string(), addr.clone()), &Empty {})?;
+
+        Ok(Response::new()
+            .add_attribute("action", "grant role")
+            .add_attribute("role", role.to_string())
+            .add_attribute("addr", addr))
+    }
+
+    pub fn revoke_role(
+        deps: DepsMut,
+        info: MessageInfo,
+        role: Role,
+        addr: Addr,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.as_ref(), Role::Admin, &info.sender)?;
+        ROLES.remove(deps.storage, (role.to_string(), addr.clone()));
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke role")
+            .add_attribute("role", role.to_string())
+            .add_attribute("addr", addr))
+    }
+
+    pub fn resolve_dispute(
+        mut deps: DepsMut,
+        info: MessageInfo,
+        job_id: Uint128,
+        is_accept: bool,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.as_ref(), Role::Arbiter, &info.sender)?;
+        let mut dispute = DISPUTES
+            .may_load(deps.storage, job_key(job_id)?)?
+            .ok_or(ContractError::DisputeNotFound { job_id })?;
+        if dispute.outcome.is_some() {
+            return Err(ContractError::DisputeFinalized {});
+        }
+        // the escrow and the collateral a rejection slashes count against the limit of their asset,
+        // jurors who already decided a round are not overruled
+        let job = load_job(deps.as_ref(), job_id)?;
+        let escrow = load_stake(deps.storage, &USER_STAKE, &job.owner, &job)?;
+        let mut at_stake: AssetTotals = vec![];
+        add_to_asset(&mut at_stake, &escrow.asset, escrow.amount);
+        for (agent, _) in job_agents(deps.storage, job_id)? {
+            let collateral = load_stake(deps.storage, &AGENT_STAKE, &agent, &job)?;
+            add_to_asset(&mut at_stake, &collateral.asset, collateral.amount);
+        }
+        for (asset, amount) in at_stake {
+            let max_escrow = ARBITER_MAX_ESCROW
+                .may_load(deps.storage, asset.to_string())?
+                .map(|limit| limit.amount)
+                .unwrap_or_default();
+            if max_escrow.is_zero() || amount > max_escrow || !dispute.rounds.is_empty() {
+                return Err(ContractError::NotArbitrable { asset: asset.to_string(), max_escrow });
+            }
+        }
+
+        // recorded as a round without panel, so nobody shares the dispute fee
+        let outcome = if is_accept { DisputeOutcome::Accepted } else { DisputeOutcome::Rejected };
+        dispute.rounds.push(DisputeRound::without_panel(outcome));
+        let dispute_config = DISPUTE_CONFIG.load(deps.storage)?;
+        let event = settle_dispute(deps.branch(), &mut dispute, &dispute_config)?;
+        DISPUTES.save(deps.storage, job_key(job_id)?, &dispute)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "resolve dispute")
+            .add_attribute("job_id", job_id.to_string())
+            .add_attribute("arbiter", info.sender)
+            .add_event(event))
+    }
+
+    pub fn pause(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        flags: PauseFlags,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.as_ref(), Role::Guardian, &info.sender)?;
+        let mut paused = PAUSED.may_load(deps.storage)?.unwrap_or_default();
+        if flags.voting && !paused.voting {
+            VOTING_PAUSED_AT.save(deps.storage, &env.block.time)?;
+        }
+        paused.staking |= flags.staking;
+        paused.payouts |= flags.payouts;
+        paused.voting |= flags.voting;
+        paused.withdrawals |= flags.withdrawals;
+        PAUSED.save(deps.storage, &paused)?;
+
+        Ok(pause_response("pause", &paused))
+    }
+
+    pub fn unpause(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        flags: PauseFlags,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.as_ref(), Role::Guardian, &info.sender)?;
+        let mut paused = PAUSED.may_load(deps.storage)?.unwrap_or_default();
+        let extended_by = if flags.voting && paused.voting {
+            Some(extend_dispute_deadlines(deps.storage, &env)?)
+        } else {
+            None
+        };
+        paused.staking &= !flags.staking;
+        paused.payouts &= !flags.payouts;
+        paused.voting &= !flags.voting;
+        paused.withdrawals &= !flags.withdrawals;
+        PAUSED.save(deps.storage, &paused)?;
+
+        let mut response = pause_response("unpause", &paused);
+        if let Some(seconds) = extended_by {
+            response = response.add_attribute("deadlines_extended_by", seconds.to_string());
+        }
+        Ok(response)
+    }
+
+    // nobody could act on a dispute while voting was paused, every deadline still running is
+    // pushed back by the paused time, returns it in seconds
+    fn extend_dispute_deadlines(storage: &mut dyn Storage, env: &Env) -> StdResult<u64> {
+        let Some(paused_at) = VOTING_PAUSED_AT.may_load(storage)? else {
+            return Ok(0);
+        };
+        VOTING_PAUSED_AT.remove(storage);
+        let paused_for = env.block.time.seconds().saturating_sub(paused_at.seconds());
+        let job_ids = OPEN_DISPUTES
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for job_id in job_ids {
+            let mut dispute = DISPUTES.load(storage, job_id)?;
+            if let Some(draw) = dispute.draw.as_mut() {
+                draw.deadline = draw.deadline.plus_seconds(paused_for);
+            }
+            dispute.commit_deadline = dispute.commit_deadline.plus_seconds(paused_for);
+            dispute.reveal_deadline = dispute.reveal_deadline.plus_seconds(paused_for);
+            dispute.appeal_deadline = dispute.appeal_deadline.map(|deadline| deadline.plus_seconds(paused_for));
+            DISPUTES.save(storage, job_id, &dispute)?;
+        }
+        Ok(paused_for)
+    }
+
+    fn pause_response(action: &str, paused: &PauseFlags) -> Response {
+        Response::new()
+            .add_attribute("action", action)
+            .add_attribute("staking", paused.staking.to_string())
+            .add_attribute("payouts", paused.payouts.to_string())
+            .add_attribute("voting", paused.voting.to_string())
+            .add_attribute("withdrawals", paused.withdrawals.to_string())
+    }
+
+    pub fn add_asset(
+        deps: DepsMut,
+        info: MessageInfo,
+        token_denom: String,
+        asset: Asset,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.as_ref(), Role::Admin, &info.sender)?;
+        validate_asset(deps.api, &asset)?;
+        ACCEPTED_ASSETS.save(
+            deps.storage,
+            asset.to_string(),
+            &TokenInfo { token_denom, asset: asset.clone() },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "add asset")
+            .add_attribute("asset", asset.to_string()))
+    }
+
+    pub fn remove_asset(
+        deps: DepsMut,
+        info: MessageInfo,
+        asset: Asset,
+    ) -> Result<Response, ContractError> {
+        ensure_role(deps.as_ref(), Role::Admin, &info.sender)?;
+        if !ACCEPTED_ASSETS.has(deps.storage, asset.to_string()) {
+            return Err(ContractError::AssetNotAccepted { asset: asset.to_string() });
+        }
+        if asset == BOND_ASSET.load(deps.storage)? {
+            return Err(ContractError::BondAssetNotRemovable { asset: asset.to_string() });
+        }
+        ACCEPTED_ASSETS.remove(deps.storage, asset.to_string());
+        ARBITER_MAX_ESCROW.remove(deps.storage, asset.to_string());
+
+        Ok(Response::new()
+            .add_attribute("action", "remove asset")
+            .add_attribute("asset", asset.to_string()))
+    }
+}
+
+fn load_job(deps: Deps, job_id: Uint128) -> Result<Job, ContractError> {
+    JOBS.may_load(deps.storage, job_key(job_id)?)?
+        .ok_or(ContractError::JobNotFound { job_id })
+}
+
+fn ensure_job_status(job: &Job, allowed: &[JobStatus]) -> Result<(), ContractError> {
+    if !allowed.contains(&job.status) {
+        return Err(ContractError::InvalidJobStatus {
+            job_id: job.id,
+            status: job.status.clone(),
+        });
+    }
+    Ok(())
+}
+
+// payouts move the owner's escrow, so only the owner or the arbiter they
+// approved may trigger them
+fn ensure_payout_authority(job: &Job, sender: &Addr) -> Result<(), ContractError> {
+    if job.owner != *sender && job.payout_authority.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+// the pause flag that blocks a message, admin messages and the job lifecycle that moves
+// no funds are never paused
+fn paused_operation(paused: &PauseFlags, msg: &ExecuteMsg) -> Option<&'static str> {
+    match msg {
+        ExecuteMsg::Receive(_)
+        | ExecuteMsg::UserStake { .. }
+        | ExecuteMsg::AgentStake { .. }
+        | ExecuteMsg::CommitBid { .. }
+        | ExecuteMsg::RegisterJuror { .. } if paused.staking => Some("staking"),
+        ExecuteMsg::OpenDispute { .. }
+        | ExecuteMsg::CommitSeed { .. }
+        | ExecuteMsg::RevealSeed { .. }
+        | ExecuteMsg::CommitVote { .. }
+        | ExecuteMsg::RevealVote { .. }
+        | ExecuteMsg::Appeal { .. }
+        | ExecuteMsg::FinalizeDispute { .. }
+        | ExecuteMsg::ResolveDispute { .. } if paused.voting => Some("voting"),
+        ExecuteMsg::UserUnstake { .. }
+        | ExecuteMsg::AgentUnstake { .. }
+        | ExecuteMsg::DistributeRewardsByAgent { .. }
+        | ExecuteMsg::DistributeRewardsByTime { .. }
+        | ExecuteMsg::SettleAuction { .. }
+        | ExecuteMsg::FinalizeDispute { .. }
+        | ExecuteMsg::ResolveDispute { .. } if paused.payouts => Some("payouts"),
+        ExecuteMsg::Withdraw { .. }
+        | ExecuteMsg::WithdrawJurorBond {} if paused.withdrawals => Some("withdrawals"),
+        _ => None,
+    }
+}
+
+// the admin and `Role::Admin` hold every role
+fn has_role(deps: Deps, role: Role, addr: &Addr) -> StdResult<bool> {
+    if OWNERSHIP.load(deps.storage)?.owner.as_ref() == Some(addr)
+        || ROLES.has(deps.storage, (Role::Admin.to_string(), addr.clone()))
+    {
+        return Ok(true);
+    }
+    Ok(ROLES.has(deps.storage, (role.to_string(), addr.clone())))
+}
+
+fn ensure_role(deps: Deps, role: Role, addr: &Addr) -> Result<(), ContractError> {
+    if !has_role(deps, role, addr)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+fn validate_config(api: &dyn Api, config: &Config) -> Result<(), ContractError> {
+    api.addr_validate(config.treasury.as_str())?;
+    if config.protocol_fee_bps > 10_000 {
+        return Err(ContractError::InvalidConfig {
+            reason: "protocol_fee_bps must not exceed 10000".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn set_arbiter_max_escrow(storage: &mut dyn Storage, limit: AssetAmount) -> Result<(), ContractError> {
+    let key = limit.asset.to_string();
+    if !ACCEPTED_ASSETS.has(storage, key.clone()) {
+        return Err(ContractError::AssetNotAccepted { asset: key });
+    }
+    if limit.amount.is_zero() {
+        ARBITER_MAX_ESCROW.remove(storage, key);
+    } else {
+        ARBITER_MAX_ESCROW.save(storage, key, &limit)?;
+    }
+    Ok(())
+}
+
+fn validate_dispute_config(api: &dyn Api, dispute_config: &DisputeConfig) -> Result<(), ContractError> {
+    if dispute_config.agent_slash_bps as u32 + dispute_config.dispute_fee_bps as u32 > 10_000 {
+        return Err(ContractError::InvalidDisputeConfig {
+            reason: "agent_slash_bps + dispute_fee_bps must not exceed 10000".to_string(),
+        });
+    }
+    if dispute_config.juror_slash_bps > 10_000 {
+        return Err(ContractError::InvalidDisputeConfig {
+            reason: "juror_slash_bps must not exceed 10000".to_string(),
+        });
+    }
+    if dispute_config.panel_size == 0 || dispute_config.quorum > dispute_config.panel_size {
+        return Err(ContractError::InvalidDisputeConfig {
+            reason: "panel_size must be at least 1 and not below quorum".to_string(),
+        });
+    }
+    if dispute_config.max_jurors < dispute_config.quorum {
+        return Err(ContractError::InvalidDisputeConfig {
+            reason: "max_jurors must not be below quorum".to_string(),
+        });
+    }
+    if dispute_config.max_appeals > 10 {
+        return Err(ContractError::InvalidDisputeConfig {
+            reason: "max_appeals must not exceed 10".to_string(),
+        });
+    }
+    if let Some(beacon) = &dispute_config.randomness_beacon {
+        api.addr_validate(beacon.as_str())?;
+    }
+    Ok(())
+}
+
+fn validate_asset(api: &dyn Api, asset: &Asset) -> StdResult<()> {
+    if let Asset::Cw20 { addr } = asset {
+        api.addr_validate(addr.as_str())?;
+    }
+    Ok(())
+}
+
+fn ensure_accepted_asset(deps: Deps, asset: &Asset) -> Result<(), ContractError> {
+    if !ACCEPTED_ASSETS.has(deps.storage, asset.to_string()) {
+        return Err(ContractError::AssetNotAccepted { asset: asset.to_string() });
+    }
+    Ok(())
+}
+
+// stake of `addr` on the job, an empty stake is reported in the job's payment asset
+fn load_stake(
+    storage: &dyn Storage,
+    stakes: &Map<(Addr, u64), AssetAmount>,
+    addr: &Addr,
+    job: &Job,
+) -> StdResult<AssetAmount> {
+    Ok(stakes
+        .may_load(storage, (addr.clone(), job_key(job.id)?))?
+        .unwrap_or(AssetAmount {
+            asset: job.payment_asset.clone(),
+            amount: Uint128::zero(),
+        }))
+}
+
+fn credit_claimable(storage: &mut dyn Storage, addr: &Addr, asset: &Asset, amount: Uint128) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+    CLAIMABLE.update(storage, (addr.clone(), asset.to_string()), |claimable| -> StdResult<_> {
+        let mut claimable = claimable.unwrap_or(AssetAmount { asset: asset.clone(), amount: Uint128::zero() });
+        claimable.amount += amount;
+        Ok(claimable)
+    })?;
+    Ok(())
+}
+
+// credit the protocol fee on `amount` to the treasury, returns the fee
+fn take_protocol_fee(storage: &mut dyn Storage, config: &Config, asset: &Asset, amount: Uint128) -> StdResult<Uint128> {
+    let fee = amount.multiply_ratio(config.protocol_fee_bps, 10_000u128);
+    if fee.is_zero() {
+        return Ok(fee);
+    }
+    credit_claimable(storage, &config.treasury, asset, fee)?;
+    PROTOCOL_FEES.update(storage, asset.to_string(), |fees| -> StdResult<_> {
+        let mut fees = fees.unwrap_or(AssetAmount { asset: asset.clone(), amount: Uint128::zero() });
+        fees.amount += fee;
+        Ok(fees)
+    })?;
+    Ok(fee)
+}
+
+fn transfer_msg(asset: &Asset, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    match asset {
+        Asset::Cw20 { addr } => {
+            let transfer_msg = cw20::Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount,
+            };
+
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: addr.to_string(),
+                msg: to_json_binary(&transfer_msg)?,
+                funds: vec![],
+            }))
+        }
+        Asset::Native { denom } => Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin::new(amount, denom)],
+        })),
+    }
+}
+
+// take `amount` from the sender: cw20 is pulled with TransferFrom from the sender's
+// allowance, native denoms must be attached to the message
+fn collect_payment(
+    asset: &Asset,
+    info: &MessageInfo,
+    contract_addr: &Addr,
+    amount: Uint128,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    match asset {
+        Asset::Cw20 { addr } => {
+            if !info.funds.is_empty() {
+                return Err(ContractError::UnexpectedFunds {});
+            }
+            if amount.is_zero() {
+                return Ok(None);
+            }
+            let transfer_from_msg = cw20::Cw20ExecuteMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: contract_addr.to_string(),
+                amount,
+            };
+
+            Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: addr.to_string(),
+                msg: to_json_binary(&transfer_from_msg)?,
+                funds: vec![],
+            })))
+        }
+        Asset::Native { denom } => {
+            let paid = match info.funds.as_slice() {
+                [] => Some(Uint128::zero()),
+                [coin] if coin.denom == *denom => Some(coin.amount),
+                _ => None,
+            };
+            if paid != Some(amount) {
+                return Err(ContractError::InvalidFunds {
+                    amount,
+                    denom: denom.clone(),
+                });
+            }
+            Ok(None)
+        }
+    }
+}
+
+fn load_auction(storage: &dyn Storage, job_id: Uint128) -> Result<Auction, ContractError> {
+    AUCTIONS
+        .may_load(storage, job_key(job_id)?)?
+        .ok_or(ContractError::AuctionNotFound { job_id })
+}
+
+// while an auction runs, the job hires through its bids only
+fn ensure_no_auction(storage: &dyn Storage, job_id: Uint128) -> Result<(), ContractError> {
+    if AUCTIONS
+        .may_load(storage, job_key(job_id)?)?
+        .is_some_and(|auction| !auction.settled)
+    {
+        return Err(ContractError::AuctionInProgress {});
+    }
+    Ok(())
+}
+
+fn is_job_agent(deps: Deps, job_id: Uint128, addr: &Addr) -> StdResult<bool> {
+    Ok(JOB_AGENTS.has(deps.storage, (job_key(job_id)?, addr.clone())))
+}
+
+// agents on the job with their slot, each agent once
+fn job_agents(storage: &dyn Storage, job_id: Uint128) -> StdResult<Vec<(Addr, AgentSlot)>> {
+    JOB_AGENTS
+        .prefix(job_key(job_id)?)
+        .range(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+fn query_beacon(deps: Deps, beacon: &Addr) -> StdResult<Binary> {
+    let randomness: RandomnessResponse = deps
+        .querier
+        .query_wasm_smart(beacon, &BeaconQueryMsg::Randomness {})?;
+    Ok(randomness.randomness)
+}
+
+// gives the place of a juror with nothing left bonded or unbonding back to the registry
+fn remove_juror(storage: &mut dyn Storage, juror_addr: &Addr) -> StdResult<()> {
+    JURORS.remove(storage, juror_addr.clone());
+    JUROR_COUNT.update(storage, |juror_count| -> StdResult<_> { Ok(juror_count.saturating_sub(1)) })?;
+    Ok(())
+}
+
+// jurors bonded at least the minimum who are not a party of the job, with their bond.
+// Goes through the whole registry, which `DisputeConfig::max_jurors` keeps small
+fn eligible_jurors(deps: Deps, job: &Job) -> StdResult<Vec<(Addr, Uint128)>> {
+    let dispute_config = DISPUTE_CONFIG.load(deps.storage)?;
+    let mut candidates = vec![];
+    for item in JURORS.range(deps.storage, None, None, Order::Ascending) {
+        let (juror_addr, juror) = item?;
+        if juror.bonded.is_zero()
+            || juror.bonded < dispute_config.min_juror_bond
+            || job.owner == juror_addr
+            || is_job_agent(deps, job.id, &juror_addr)?
+        {
+            continue;
+        }
+        candidates.push((juror_addr, juror.bonded));
+    }
+    Ok(candidates)
+}
+
+// draw up to `panel_size` of the candidates without replacement, each pick weighted by
+// the bond of the candidates left
+fn draw_panel(mut candidates: Vec<(Addr, Uint128)>, job_id: Uint128, seed: &[u8], panel_size: u32) -> Vec<Addr> {
+    let mut panel = vec![];
+    let mut draw = 0u32;
+    let mut total_bonded: Uint128 = candidates.iter().map(|(_, bonded)| *bonded).sum();
+    while draw < panel_size && !candidates.is_empty() {
+        let mut target = Uint128::new(panel_randomness(seed, job_id, draw)) % total_bonded;
+        let mut index = 0;
+        while target >= candidates[index].1 {
+            target -= candidates[index].1;
+            index += 1;
+        }
+        let (juror_addr, bonded) = candidates.remove(index);
+        total_bonded -= bonded;
+        panel.push(juror_addr);
+        draw += 1;
+    }
+    panel
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetJob { job_id } => query::get_job(deps, job_id),
+        QueryMsg::GetUserStake { user_addr , job_id} => query::get_user_stake(deps, user_addr, job_id),
+        QueryMsg::GetAgentStake { agent_addr , job_id} => query::get_agent_stake(deps, agent_addr, job_id),
+        QueryMsg::GetNumOfAgent { job_id } => query::get_num_of_agent(deps, job_id),
+        QueryMsg::GetAgentRates { job_id } => query::get_agent_rates(deps, job_id),
+        QueryMsg::GetApplication { job_id, agent } => query::get_application(deps, job_id, agent),
+        QueryMsg::ListApplications { job_id, start_after, limit } => query::list_applications(deps, job_id, start_after, limit),
+        QueryMsg::GetAuction { job_id } => to_json_binary(&AUCTIONS.load(deps.storage, job_key(job_id)?)?),
+        QueryMsg::ListBids { job_id, start_after, limit } => query::list_bids(deps, job_id, start_after, limit),
+        QueryMsg::GetAssets {} => query::get_assets(deps),
+        QueryMsg::GetConfig {} => to_json_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::GetArbiterMaxEscrow {} => query::get_arbiter_max_escrow(deps),
+        QueryMsg::GetPauseFlags {} => to_json_binary(&PAUSED.may_load(deps.storage)?.unwrap_or_default()),
+        QueryMsg::GetOwnership {} => to_json_binary(&OWNERSHIP.load(deps.storage)?),
+        QueryMsg::HasRole { role, addr } => to_json_binary(&has_role(deps, role, &addr)?),
+        QueryMsg::ListRoleMembers { role, start_after, limit } => query::list_role_members(deps, role, start_after, limit),
+        QueryMsg::GetProtocolFees {} => query::get_protocol_fees(deps),
+        QueryMsg::CheckIfEnoughRewards {job_id} => to_json_binary(&query::check_if_enough_rewards(
+            deps,
+            job_id
+        )?),
+        QueryMsg::GetVoteResult { job_id } => query::get_vote_result(deps, job_id),
+        QueryMsg::GetDispute { job_id } => query::get_dispute(deps, job_id),
+        QueryMsg::ListDisputes { start_after, limit } => query::list_disputes(deps, start_after, limit),
+        QueryMsg::GetJuror { juror_addr } => query::get_juror(deps, juror_addr),
+        QueryMsg::ListJurors { start_after, limit } => query::list_jurors(deps, start_after, limit),
+        QueryMsg::GetClaimable { addr } => query::get_claimable(deps, addr),
+    }
+}
+
+pub mod query {
+    use super::*;
+    use cosmwasm_std::Order;
+    use cw_storage_plus::Bound;
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
+
+    pub fn get_job(deps: Deps, job_id: Uint128) -> StdResult<Binary> {
+        let job = JOBS.load(deps.storage, job_key(job_id)?)?;
+        to_json_binary(&JobResponse {
+            id: job.id,
+            owner: job.owner,
+            title: job.title,
+            spec_uri: job.spec_uri,
+            spec_hash: job.spec_hash,
+            budget: job.budget,
+            payment_asset: job.payment_asset,
+            created_at: job.created_at,
+            status: job.status,
+            payout_authority: job.payout_authority,
+            min_agent_stake: job.min_agent_stake,
+            max_agents: job.max_agents,
+        })
+    }
+
+    pub fn get_user_stake(deps: Deps, user_addr: Addr, job_id: Uint128) -> StdResult<Binary> {
+        let job = JOBS.load(deps.storage, job_key(job_id)?)?;
+        to_json_binary(&load_stake(deps.storage, &USER_STAKE, &user_addr, &job)?)
+    }
+
+    pub fn get_agent_stake(deps: Deps, agent_addr: Addr, job_id: Uint128) -> StdResult<Binary> {
+        let job = JOBS.load(deps.storage, job_key(job_id)?)?;
+        to_json_binary(&load_stake(deps.storage, &AGENT_STAKE, &agent_addr, &job)?)
+    }
+
+    pub fn get_num_of_agent(deps: Deps, job_id: Uint128) -> StdResult<Binary> {
+        let job_agent_addrs = job_agents(deps.storage, job_id)?;
+        to_json_binary(&Uint128::new(job_agent_addrs.len() as u128))
+    }
+
+    pub fn get_agent_rates(deps: Deps, job_id: Uint128) -> StdResult<Binary> {
+        let rates = job_agents(deps.storage, job_id)?
+            .into_iter()
+            .map(|(agent, slot)| AgentRateResponse { agent, cost_per_unit_time: slot.cost_per_unit_time })
+            .collect::<Vec<_>>();
+        to_json_binary(&rates)
+    }
+
+    pub fn get_application(deps: Deps, job_id: Uint128, agent: Addr) -> StdResult<Binary> {
+        let application = APPLICATIONS.load(deps.storage, (job_key(job_id)?, agent.clone()))?;
+        to_json_binary(&application_response(agent, application))
+    }
+
+    pub fn list_applications(
+        deps: Deps,
+        job_id: Uint128,
+        start_after: Option<Addr>,
+        limit: Option<u32>
+    ) -> StdResult<Binary> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let applications = APPLICATIONS
+            .prefix(job_key(job_id)?)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(agent, application)| application_response(agent, application)))
+            .collect::<StdResult<Vec<_>>>()?;
+        to_json_binary(&ListApplicationsResponse { applications })
+    }
+
+    pub fn list_bids(
+        deps: Deps,
+        job_id: Uint128,
+        start_after: Option<Addr>,
+        limit: Option<u32>
+    ) -> StdResult<Binary> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let bids = BIDS
+            .prefix(job_key(job_id)?)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(bidder, bid)| BidResponse {
+                    bidder,
+                    commitment: bid.commitment,
+                    deposit: bid.deposit,
+                    committed_at: bid.committed_at,
+                    rate: bid.rate,
+                    collateral: bid.collateral,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        to_json_binary(&ListBidsResponse { bids })
+    }
+
+    pub fn get_assets(deps: Deps) -> StdResult<Binary> {
+        let accepted_assets = ACCEPTED_ASSETS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                item.map(|(_, token_info)| TokenInfoResponse {
+                    token_denom: token_info.token_denom,
+                    asset: token_info.asset,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        to_json_binary(&AssetsResponse {
+            accepted_assets,
+            bond_asset: BOND_ASSET.load(deps.storage)?,
+        })
+    }
+
+    pub fn get_protocol_fees(deps: Deps) -> StdResult<Binary> {
+        let fees = PROTOCOL_FEES
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, fees)| fees))
+            .collect::<StdResult<Vec<_>>>()?;
+        to_json_binary(&fees)
+    }
+
+    pub fn check_if_enough_rewards(
+        deps: Deps,
+        job_id: Uint128
+    ) -> StdResult<bool> {
+        let job = JOBS.load(deps.storage, job_key(job_id)?)?;
+        let job_agent_addrs = job_agents(deps.storage, job_id)?;
+        let rewards_owner_stake_amount = USER_STAKE
+            .load(deps.storage, (job.owner, job_key(job_id)?))
+            .map(|stake| stake.amount)
+            .unwrap_or(Uint128::zero());
+        let mut total_cost_per_unit_time = Uint128::zero();
+        for (_, slot) in job_agent_addrs {
+            total_cost_per_unit_time += slot.cost_per_unit_time;
+        }
+        Ok(rewards_owner_stake_amount >= total_cost_per_unit_time)
+    }
+
+    pub fn get_vote_result(deps: Deps, job_id: Uint128) -> StdResult<Binary> {
+        let dispute = DISPUTES.load(deps.storage, job_key(job_id)?)?;
+        let vote_result = VoteResultResponse {
+            accept_vote: dispute.accept_votes,
+            reject_vote: dispute.reject_votes,
+            accept_weight: dispute.accept_weight,
+            reject_weight: dispute.reject_weight,
+        };
+        to_json_binary(&vote_result)
+    }
+
+    pub fn get_dispute(deps: Deps, job_id: Uint128) -> StdResult<Binary> {
+        let dispute = DISPUTES.load(deps.storage, job_key(job_id)?)?;
+        to_json_binary(&dispute_response(dispute))
+    }
+
+    pub fn list_disputes(
+        deps: Deps,
+        start_after: Option<Uint128>,
+        limit: Option<u32>
+    ) -> StdResult<Binary> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(job_key).transpose()?.map(Bound::exclusive);
+        let disputes = DISPUTES
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, dispute)| dispute_response(dispute)))
+            .collect::<StdResult<Vec<_>>>()?;
+        to_json_binary(&ListDisputesResponse { disputes })
+    }
+
+    pub fn get_juror(deps: Deps, juror_addr: Addr) -> StdResult<Binary> {
+        let juror = JURORS.load(deps.storage, juror_addr.clone())?;
+        to_json_binary(&juror_response(juror_addr, juror))
+    }
+
+    pub fn list_jurors(
+        deps: Deps,
+        start_after: Option<Addr>,
+        limit: Option<u32>
+    ) -> StdResult<Binary> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let jurors = JURORS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(addr, juror)| juror_response(addr, juror)))
+            .collect::<StdResult<Vec<_>>>()?;
+        to_json_binary(&ListJurorsResponse { jurors })
+    }
+
+    pub fn list_role_members(
+        deps: Deps,
+        role: Role,
+        start_after: Option<Addr>,
+        limit: Option<u32>
+    ) -> StdResult<Binary> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let members = ROLES
+            .prefix(role.to_string())
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        to_json_binary(&RoleMembersResponse { members })
+    }
+
+    pub fn get_arbiter_max_escrow(deps: Deps) -> StdResult<Binary> {
+        let limits = ARBITER_MAX_ESCROW
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, limit)| limit))
+            .collect::<StdResult<Vec<_>>>()?;
+        to_json_binary(&limits)
+    }
+
+    pub fn get_claimable(deps: Deps, addr: Addr) -> StdResult<Binary> {
+        let claimable = CLAIMABLE
+            .prefix(addr)
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, claimable)| claimable))
+            .collect::<StdResult<Vec<_>>>()?;
+        to_json_binary(&claimable)
+    }
+
+    fn application_response(agent: Addr, application: Application) -> ApplicationResponse {
+        ApplicationResponse {
+            agent,
+            proposed_rate: application.proposed_rate,
+            proposal_uri: application.proposal_uri,
+            applied_at: application.applied_at,
+            status: application.status,
+        }
+    }
+
+    fn juror_response(address: Addr, juror: Juror) -> JurorResponse {
+        JurorResponse {
+            address,
+            bonded: juror.bonded,
+            unbonding: juror.unbonding,
+            unbonding_release_at: juror.unbonding_release_at,
+            registered_at: juror.registered_at,
+        }
+    }
+
+    fn dispute_response(dispute: Dispute) -> DisputeResponse {
+        DisputeResponse {
+            job_id: dispute.job_id,
+            opener: dispute.opener,
+            reason: dispute.reason,
+            draw: dispute.draw,
+            round: dispute.round,
+            panel: dispute.panel,
+            commits: dispute.commits,
+            accept_votes: dispute.accept_votes,
+            reject_votes: dispute.reject_votes,
+            accept_weight: dispute.accept_weight,
+            reject_weight: dispute.reject_weight,
+            opened_at: dispute.opened_at,
+            commit_deadline: dispute.commit_deadline,
+            reveal_deadline: dispute.reveal_deadline,
+            rounds: dispute.rounds,
+            appeal_deadline: dispute.appeal_deadline,
+            outcome: dispute.outcome,
+        }
+    }
+}
+
+// refuses other contracts and downgrades, the unversioned first deployment
+// is converted to the current layout
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous = match cw2::CONTRACT.may_load(deps.storage)? {
+        Some(stored) => {
+            if stored.contract != CONTRACT_NAME {
+                return Err(ContractError::CannotMigrate { contract: stored.contract });
+            }
+            if Version::parse(&stored.version)? > Version::parse(CONTRACT_VERSION)? {
+                return Err(ContractError::CannotDowngrade {
+                    stored: stored.version,
+                    current: CONTRACT_VERSION.to_string(),
+                });
+            }
+            stored.version
+        }
+        None => {
+            let settings = msg.baseline.ok_or(ContractError::BaselineSettingsRequired {})?;
+            migrate::from_baseline(deps.branch(), &env, settings)?;
+            "baseline".to_string()
+        }
+    };
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", previous)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+pub mod migrate {
+    use super::*;
+    use crate::msg::BaselineSettings;
+    use cw_storage_plus::{Item, KeyDeserialize, PrimaryKey};
+    use serde::{de::DeserializeOwned, Serialize};
+
+    #[cw_serde]
+    struct BaselineTokenInfo {
+        token_denom: String,
+        token_address: Addr,
+    }
+
+    // layout of the first deployment: stakes were plain cw20 amounts keyed by the decimal
+    // string of the job id, jobs only existed as the owner of the first user stake, the agents
+    // of a job were a list and a single tally was shared by every job
+    const BASELINE_TOKEN_INFO: Item<BaselineTokenInfo> = Item::new("token_info");
+    const BASELINE_USER_STAKE: Map<(Addr, String), Uint128> = Map::new("user_stake");
+    const BASELINE_AGENT_STAKE: Map<(Addr, String), Uint128> = Map::new("agent_stake");
+    const BASELINE_JOB_OWNER: Map<String, Addr> = Map::new("job_owner");
+    const BASELINE_JOB_AGENT: Map<String, Vec<Addr>> = Map::new("job_agent");
+    const BASELINE_AGENT_COST: Map<Addr, Uint128> = Map::new("agent_cost");
+    const BASELINE_ACCEPT_VOTE: Item<Uint128> = Item::new("accpect_vote");
+    const BASELINE_REJECT_VOTE: Item<Uint128> = Item::new("reject_vote");
+    const BASELINE_IS_JUROR_VOTED: Map<Addr, bool> = Map::new("is_juror_voted");
+
+    /// Converts the first deployment to the current layout
+    pub fn from_baseline(deps: DepsMut, env: &Env, settings: BaselineSettings) -> Result<(), ContractError> {
+        let config = Config {
+            treasury: settings.treasury.unwrap_or(settings.admin.clone()),
+            protocol_fee_bps: settings.protocol_fee_bps,
+        };
+        validate_config(deps.api, &config)?;
+        CONFIG.save(deps.storage, &config)?;
+        let owner = deps.api.addr_validate(settings.admin.as_str())?;
+        OWNERSHIP.save(deps.storage, &Ownership { owner: Some(owner), ..Ownership::default() })?;
+        let dispute_config = settings.dispute_config.unwrap_or_default();
+        validate_dispute_config(deps.api, &dispute_config)?;
+        DISPUTE_CONFIG.save(deps.storage, &dispute_config)?;
+
+        // the cw20 token becomes the only accepted asset, arbiters start disabled
+        let token_info = BASELINE_TOKEN_INFO.load(deps.storage)?;
+        BASELINE_TOKEN_INFO.remove(deps.storage);
+        let asset = Asset::Cw20 { addr: token_info.token_address };
+        ACCEPTED_ASSETS.save(
+            deps.storage,
+            asset.to_string(),
+            &TokenInfo { token_denom: token_info.token_denom, asset: asset.clone() },
+        )?;
+        BOND_ASSET.save(deps.storage, &asset)?;
+
+        // the stakes keep their namespaces under `u64` keys, so each map is read in full and
+        // cleared before the entries are written back. Job ids were picked by the stakers,
+        // the stakes of a job whose id does not fit a job key are credited back
+        for (baseline_stakes, stakes) in [(BASELINE_USER_STAKE, USER_STAKE), (BASELINE_AGENT_STAKE, AGENT_STAKE)] {
+            let entries = drain(deps.storage, &baseline_stakes)?;
+            for ((addr, key), amount) in entries {
+                match parse_job_key(&key) {
+                    Some(job_id) => stakes.save(deps.storage, (addr, job_id), &AssetAmount { asset: asset.clone(), amount })?,
+                    None => credit_claimable(deps.storage, &addr, &asset, amount)?,
+                }
+            }
+        }
+
+        // every job with an owner becomes an open job funded by the owner stake, `CreateJob`
+        // skips the ids they keep
+        let owners = drain(deps.storage, &BASELINE_JOB_OWNER)?;
+        for (job_id, owner) in owners.into_iter().filter_map(|(key, owner)| Some((parse_job_key(&key)?, owner))) {
+            let budget = USER_STAKE
+                .may_load(deps.storage, (owner.clone(), job_id))?
+                .map(|stake| stake.amount)
+                .unwrap_or_default();
+            let job = Job {
+                id: Uint128::from(job_id),
+                owner,
+                title: String::new(),
+                spec_uri: String::new(),
+                spec_hash: None,
+                budget,
+                payment_asset: asset.clone(),
+                created_at: env.block.time,
+                status: JobStatus::Open,
+                payout_authority: None,
+                min_agent_stake: Uint128::zero(),
+                max_agents: None,
+            };
+            JOBS.save(deps.storage, job_id, &job)?;
+        }
+
+        // every agent listed on a funded job is hired once, at the last rate it quoted. Agents of
+        // a job nobody funded could never unstake, their stake is credited back
+        let job_agents = drain(deps.storage, &BASELINE_JOB_AGENT)?;
+        for (job_id, agents) in job_agents.into_iter().filter_map(|(key, agents)| Some((parse_job_key(&key)?, agents))) {
+            let Some(job) = JOBS.may_load(deps.storage, job_id)? else {
+                for agent in agents {
+                    if let Some(stake) = AGENT_STAKE.may_load(deps.storage, (agent.clone(), job_id))? {
+                        credit_claimable(deps.storage, &agent, &stake.asset, stake.amount)?;
+                        AGENT_STAKE.remove(deps.storage, (agent, job_id));
+                    }
+                }
+                continue;
+            };
+            let mut hires = 0u32;
+            for agent in agents {
+                if JOB_AGENTS.has(deps.storage, (job_id, agent.clone())) {
+                    continue;
+                }
+                let cost_per_unit_time = BASELINE_AGENT_COST.may_load(deps.storage, agent.clone())?.unwrap_or_default();
+                JOB_AGENTS.save(deps.storage, (job_id, agent.clone()), &AgentSlot { cost_per_unit_time })?;
+                let application = Application {
+                    proposed_rate: cost_per_unit_time,
+                    proposal_uri: String::new(),
+                    applied_at: job.created_at,
+                    status: ApplicationStatus::Accepted,
+                };
+                APPLICATIONS.save(deps.storage, (job_id, agent), &application)?;
+                hires += 1;
+            }
+            JOB_HIRES.save(deps.storage, job_id, &hires)?;
+        }
+        BASELINE_AGENT_COST.clear(deps.storage);
+
+        // the shared tally was replaced by one dispute per job
+        BASELINE_ACCEPT_VOTE.remove(deps.storage);
+        BASELINE_REJECT_VOTE.remove(deps.storage);
+        BASELINE_IS_JUROR_VOTED.clear(deps.storage);
+        Ok(())
+    }
+
+    fn drain<'a, K, T>(storage: &mut dyn Storage, map: &Map<K, T>) -> StdResult<Vec<(K::Output, T)>>
+    where
+        K: PrimaryKey<'a> + KeyDeserialize,
+        K::Output: 'static,
+        T: Serialize + DeserializeOwned,
+    {
+        let entries = map
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        map.clear(storage);
+        Ok(entries)
+    }
+
+    // `None` for ids past the job key range
+    fn parse_job_key(key: &str) -> Option<u64> {
+        key.parse::<u64>().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{coins, Addr, Empty, Uint128};
+    use cw_multi_test::error::AnyResult;
+    use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
+
+    use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+    use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
+
+
+    use super::*;
+
+    fn setup_cw20_contract(app: &mut App, admin: Addr) -> Addr {
+        let cw20_code = ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        );
+        let cw20_code_id = app.store_code(Box::new(cw20_code));
+
+        app
+            .instantiate_contract(
+                cw20_code_id,
+                admin.clone(),
+                &Cw20InstantiateMsg {
+                    name: "Test Token".to_string(),
+                    symbol: "TTK".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![Cw20Coin {
+                        address: admin.to_string(),
+                        amount: Uint128::new(1000000),
+                    }],
+                    mint: None,
+                    marketing: None,
+                },
+                &[],
+                "CW20 Test Token",
+                None,
+            )
+            .unwrap()
     }
 
     fn setup_agent_work_contract(app: &mut App, admin: Addr, cw20_addr: Addr) -> Addr {
+        setup_agent_work_contract_with_config(app, admin, Asset::Cw20 { addr: cw20_addr }, None)
+    }
+
+    fn setup_agent_work_contract_with_config(
+        app: &mut App,
+        admin: Addr,
+        asset: Asset,
+        dispute_config: Option<DisputeConfig>,
+    ) -> Addr {
         let agent_work_code = ContractWrapper::new(execute, instantiate, query);
         let agent_work_code_id = app.store_code(Box::new(agent_work_code));
 
-        let agent_work_addr = app
-            .instantiate_contract(
-                agent_work_code_id,
-                admin.clone(),
-                &InstantiateMsg {
-                    token_symbol: "TTK".to_string(),
-                    token_contract_addr: cw20_addr.clone(),
+        app
+            .instantiate_contract(
+                agent_work_code_id,
+                admin.clone(),
+                &InstantiateMsg {
+                    accepted_assets: vec![TokenInfo {
+                        token_denom: "TTK".to_string(),
+                        asset: asset.clone(),
+                    }],
+                    bond_asset: asset,
+                    dispute_config,
+                    treasury: None,
+                    protocol_fee_bps: 0,
+                    guardian: None,
+                    arbiter_max_escrow: vec![],
+                },
+                &[],
+                "Agent Work",
+                None,
+            )
+            .unwrap()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn allocate_token(
+        app: &mut App,
+        admin: Addr,
+        cw20_addr: Addr,
+        user1: Addr,
+        user2: Addr,
+        agent1: Addr,
+        agent2: Addr,
+        agent3: Addr,
+    ) {
+        // send 500 TTK to user1, user2, agent1, agent2, agent3
+        app.execute_contract(
+            admin.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: user1.to_string(),
+                amount: Uint128::new(500),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            admin.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: user2.to_string(),
+                amount: Uint128::new(500),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            admin.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: agent1.to_string(),
+                amount: Uint128::new(500),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            admin.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: agent2.to_string(),
+                amount: Uint128::new(500),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            admin.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: agent3.to_string(),
+                amount: Uint128::new(500),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // jobs are paid in the bond asset unless a test picks another one
+    fn create_job(app: &mut App, agent_work_addr: Addr, owner: Addr) -> Uint128 {
+        let assets: AssetsResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAssets {})
+            .unwrap();
+        create_job_in(app, agent_work_addr, owner, assets.bond_asset)
+    }
+
+    fn create_job_in(app: &mut App, agent_work_addr: Addr, owner: Addr, payment_asset: Asset) -> Uint128 {
+        let response = app
+            .execute_contract(
+                owner,
+                agent_work_addr,
+                &ExecuteMsg::CreateJob {
+                    title: "Test Job".to_string(),
+                    spec_uri: "ipfs://job-spec".to_string(),
+                    spec_hash: None,
+                    budget: Uint128::new(100),
+                    payment_asset,
+                    min_agent_stake: Uint128::zero(),
+                    max_agents: None,
+                },
+                &[],
+            )
+            .unwrap();
+        let job_id = response
+            .events
+            .iter()
+            .flat_map(|e| e.attributes.iter())
+            .find(|attr| attr.key == "job_id")
+            .unwrap()
+            .value
+            .clone();
+        Uint128::new(job_id.parse().unwrap())
+    }
+
+    fn user_stake(app: &mut App, cw20_addr: Addr, agent_work_addr: Addr, user: Addr, job_id: Uint128, amount: u128) {
+        app.execute_contract(
+            user.clone(),
+            cw20_addr,
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(amount),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            user,
+            agent_work_addr,
+            &ExecuteMsg::UserStake {
+                amount: Uint128::new(amount),
+                job_id,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // agent applies at `rate` and the job owner accepts it
+    fn hire_agent(app: &mut App, agent_work_addr: Addr, agent: Addr, job_id: Uint128, rate: u128) {
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id })
+            .unwrap();
+        app.execute_contract(
+            agent.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::ApplyToJob {
+                job_id,
+                proposed_rate: Uint128::new(rate),
+                proposal_uri: "ipfs://proposal".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            job.owner,
+            agent_work_addr,
+            &ExecuteMsg::AcceptApplication { job_id, agent },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // hires the agent at a rate of 10 unless it was already hired
+    fn agent_stake(app: &mut App, cw20_addr: Addr, agent_work_addr: Addr, agent: Addr, job_id: Uint128, amount: u128) {
+        let hired = app
+            .wrap()
+            .query_wasm_smart::<ApplicationResponse>(&agent_work_addr, &QueryMsg::GetApplication { job_id, agent: agent.clone() })
+            .is_ok_and(|application| application.status == ApplicationStatus::Accepted);
+        if !hired {
+            hire_agent(app, agent_work_addr.clone(), agent.clone(), job_id, 10);
+        }
+        app.execute_contract(
+            agent.clone(),
+            cw20_addr,
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(amount),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            agent,
+            agent_work_addr,
+            &ExecuteMsg::AgentStake {
+                amount: Uint128::new(amount),
+                job_id,
+                collateral_asset: None,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    fn register_juror(app: &mut App, cw20_addr: Addr, agent_work_addr: Addr, juror: Addr, amount: u128) {
+        app.execute_contract(
+            juror.clone(),
+            cw20_addr,
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(amount),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            juror,
+            agent_work_addr,
+            &ExecuteMsg::RegisterJuror {
+                amount: Uint128::new(amount),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // open a dispute and draw its panel from the jurors registered so far: the opener commits
+    // to "seed", the other side to "other seed" and both reveal
+    fn open_dispute(app: &mut App, agent_work_addr: Addr, opener: Addr, job_id: Uint128) {
+        app.execute_contract(
+            opener.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::OpenDispute {
+                job_id,
+                reason: "work is incomplete".to_string(),
+                seed_hash: seed_commitment("seed"),
+            },
+            &[],
+        )
+        .unwrap();
+        complete_draw(app, agent_work_addr, opener, job_id);
+    }
+
+    fn complete_draw(app: &mut App, agent_work_addr: Addr, opener: Addr, job_id: Uint128) {
+        let other_side = other_side(app, &agent_work_addr, &opener, job_id);
+        commit_seed(app, agent_work_addr.clone(), other_side.clone(), job_id).unwrap();
+        reveal_seed(app, agent_work_addr.clone(), opener, job_id, "seed").unwrap();
+        reveal_seed(app, agent_work_addr, other_side, job_id, "other seed").unwrap();
+    }
+
+    // the owner when an agent opened the dispute, the first agent of the job otherwise
+    fn other_side(app: &App, agent_work_addr: &Addr, opener: &Addr, job_id: Uint128) -> Addr {
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(agent_work_addr, &QueryMsg::GetJob { job_id })
+            .unwrap();
+        if job.owner != *opener {
+            return job.owner;
+        }
+        let rates: Vec<AgentRateResponse> = app
+            .wrap()
+            .query_wasm_smart(agent_work_addr, &QueryMsg::GetAgentRates { job_id })
+            .unwrap();
+        rates[0].agent.clone()
+    }
+
+    fn commit_seed(app: &mut App, agent_work_addr: Addr, party: Addr, job_id: Uint128) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            party,
+            agent_work_addr,
+            &ExecuteMsg::CommitSeed {
+                job_id,
+                seed_hash: seed_commitment("other seed"),
+            },
+            &[],
+        )
+    }
+
+    fn reveal_seed(app: &mut App, agent_work_addr: Addr, party: Addr, job_id: Uint128, seed: &str) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            party,
+            agent_work_addr,
+            &ExecuteMsg::RevealSeed {
+                job_id,
+                seed: seed.to_string(),
+            },
+            &[],
+        )
+    }
+
+    // each juror uses its own address as salt, on every round
+    fn commit_vote(app: &mut App, agent_work_addr: Addr, juror: Addr, job_id: Uint128, is_accept: bool) -> AnyResult<AppResponse> {
+        let round = app
+            .wrap()
+            .query_wasm_smart::<DisputeResponse>(&agent_work_addr, &QueryMsg::GetDispute { job_id })
+            .map_or(0, |dispute| dispute.round);
+        app.execute_contract(
+            juror.clone(),
+            agent_work_addr,
+            &ExecuteMsg::CommitVote {
+                job_id,
+                hash: vote_commitment(job_id, round, &juror, is_accept, juror.as_str()),
+            },
+            &[],
+        )
+    }
+
+    fn reveal_vote(app: &mut App, agent_work_addr: Addr, juror: Addr, job_id: Uint128, is_accept: bool) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            juror.clone(),
+            agent_work_addr,
+            &ExecuteMsg::RevealVote {
+                job_id,
+                is_accept,
+                salt: juror.to_string(),
+            },
+            &[],
+        )
+    }
+
+    // jump to the reveal period of a dispute opened at the current block time
+    fn start_reveal_period(app: &mut App) {
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(DisputeConfig::default().commit_period)
+        });
+    }
+
+    // jump past the appeal period of a round tallied at the current block time
+    fn end_appeal_period(app: &mut App) {
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(DisputeConfig::default().appeal_period)
+        });
+    }
+
+    // move a job from Open to Submitted: owner starts it and agent submits
+    fn submit_job(app: &mut App, agent_work_addr: Addr, owner: Addr, agent: Addr, job_id: Uint128) {
+        app.execute_contract(
+            owner,
+            agent_work_addr.clone(),
+            &ExecuteMsg::StartJob { job_id },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            agent,
+            agent_work_addr,
+            &ExecuteMsg::SubmitWork { job_id },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // move a job from Open to Completed: submitted work is accepted by the owner
+    fn complete_job(app: &mut App, agent_work_addr: Addr, owner: Addr, agent: Addr, job_id: Uint128) {
+        submit_job(app, agent_work_addr.clone(), owner.clone(), agent, job_id);
+        app.execute_contract(
+            owner,
+            agent_work_addr,
+            &ExecuteMsg::CompleteJob { job_id },
+            &[],
+        )
+        .unwrap();
+    }
+
+    fn assert_invalid_job_status(err: ContractError, expected: JobStatus) {
+        match err {
+            ContractError::InvalidJobStatus { status, .. } => assert_eq!(status, expected),
+            err => panic!("unexpected error: {err}"),
+        }
+    }
+
+    #[test]
+    fn test_create_job() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+
+        // set up cw20 contract
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        // set up agent work contract
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+
+        // job ids are assigned sequentially by the contract
+        let job_1 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        let job_2 = create_job(&mut app, agent_work_addr.clone(), user2.clone());
+        assert_eq!(job_1, Uint128::new(1));
+        assert_eq!(job_2, Uint128::new(2));
+
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id: job_1 })
+            .unwrap();
+        assert_eq!(job.id, job_1);
+        assert_eq!(job.owner, user1);
+        assert_eq!(job.title, "Test Job");
+        assert_eq!(job.spec_uri, "ipfs://job-spec");
+        assert_eq!(job.budget, Uint128::new(100));
+        assert_eq!(job.created_at, app.block_info().time);
+        assert_eq!(job.status, JobStatus::Open);
+
+        // staking on a job that was never created is rejected
+        let err = app
+            .execute_contract(
+                user1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::UserStake {
+                    amount: Uint128::new(10),
+                    job_id: Uint128::new(3),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::JobNotFound { .. }
+        ));
+
+        // only the job owner can fund the job
+        let err = app
+            .execute_contract(
+                user2.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::UserStake {
+                    amount: Uint128::new(10),
+                    job_id: job_1,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::NotJobOwner {}
+        ));
+    }
+
+    #[test]
+    fn test_job_lifecycle() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+
+        // a job without agents cannot be started
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::StartJob { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NoAgents {}));
+
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 10);
+
+        // payout and completion are not possible while the job is open
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByAgent { job_id }, &[])
+            .unwrap_err();
+        assert_invalid_job_status(err.downcast().unwrap(), JobStatus::Open);
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::CompleteJob { job_id }, &[])
+            .unwrap_err();
+        assert_invalid_job_status(err.downcast().unwrap(), JobStatus::Open);
+
+        // only the owner starts the job
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::StartJob { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotJobOwner {}));
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::StartJob { job_id }, &[])
+            .unwrap();
+
+        // staking and unstaking is closed once the job is in progress
+        let err = app
+            .execute_contract(
+                agent2.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::AgentStake {
+                    amount: Uint128::new(10),
+                    job_id,
+                    collateral_asset: None,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_invalid_job_status(err.downcast().unwrap(), JobStatus::InProgress);
+        let err = app
+            .execute_contract(
+                agent1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::AgentUnstake {
+                    amount: Uint128::new(10),
+                    job_id,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_invalid_job_status(err.downcast().unwrap(), JobStatus::InProgress);
+        let err = app
+            .execute_contract(
+                user1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::UserUnstake {
+                    amount: Uint128::new(10),
+                    job_id,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_invalid_job_status(err.downcast().unwrap(), JobStatus::InProgress);
+
+        // disputes can only be raised against submitted work
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::OpenDispute { job_id, reason: "work is incomplete".to_string(), seed_hash: seed_commitment("seed") }, &[])
+            .unwrap_err();
+        assert_invalid_job_status(err.downcast().unwrap(), JobStatus::InProgress);
+
+        // only an agent of the job can submit work
+        let err = app
+            .execute_contract(agent2.clone(), agent_work_addr.clone(), &ExecuteMsg::SubmitWork { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotJobAgent {}));
+        app.execute_contract(agent1.clone(), agent_work_addr.clone(), &ExecuteMsg::SubmitWork { job_id }, &[])
+            .unwrap();
+
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::CompleteJob { job_id }, &[])
+            .unwrap();
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id })
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+
+        // rewards are paid once
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByAgent { job_id }, &[])
+            .unwrap();
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByAgent { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NothingToDistribute {}));
+
+        // the payout is credited and the agent withdraws it
+        let ttk = Asset::Cw20 { addr: cw20_addr.clone() };
+        let claimable: Vec<AssetAmount> = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetClaimable { addr: agent1.clone() })
+            .unwrap();
+        assert_eq!(claimable, vec![AssetAmount { asset: ttk.clone(), amount: Uint128::new(110) }]);
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(490));
+        let err = app
+            .execute_contract(
+                agent1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::Withdraw { asset: ttk.clone(), amount: Uint128::new(111) },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InsufficientClaimable { available } if available == Uint128::new(110)
+        ));
+        app.execute_contract(
+            agent1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::Withdraw { asset: ttk.clone(), amount: Uint128::new(60) },
+            &[],
+        )
+        .unwrap();
+        withdraw_all(&mut app, &agent_work_addr, &agent1);
+        let err = app
+            .execute_contract(
+                agent1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::Withdraw { asset: ttk, amount: Uint128::new(1) },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NothingToWithdraw {}));
+        let agent1_balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &cw20_addr,
+                &Cw20QueryMsg::Balance {
+                    address: agent1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(agent1_balance.balance, Uint128::new(600));
+    }
+
+    #[test]
+    fn test_dispute_and_cancel_job() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent3.clone(), 100);
+
+        // job 1 is disputed after the work is submitted
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::StartJob { job_id }, &[])
+            .unwrap();
+        app.execute_contract(agent1.clone(), agent_work_addr.clone(), &ExecuteMsg::SubmitWork { job_id }, &[])
+            .unwrap();
+
+        // outsiders cannot dispute the job
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::OpenDispute { job_id, reason: "work is incomplete".to_string(), seed_hash: seed_commitment("seed") }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::OpenDispute { job_id, reason: "work is incomplete".to_string(), seed_hash: seed_commitment("seed") }, &[])
+            .unwrap();
+
+        // a disputed job can neither be completed nor paid out
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::CompleteJob { job_id }, &[])
+            .unwrap_err();
+        assert_invalid_job_status(err.downcast().unwrap(), JobStatus::Disputed);
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByTime { job_id }, &[])
+            .unwrap_err();
+        assert_invalid_job_status(err.downcast().unwrap(), JobStatus::Disputed);
+
+        // job 2 is cancelled before it starts and every stake can be withdrawn
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user2.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user2.clone(), job_id, 50);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_id, 20);
+        app.execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::CancelJob { job_id }, &[])
+            .unwrap();
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::StartJob { job_id }, &[])
+            .unwrap_err();
+        assert_invalid_job_status(err.downcast().unwrap(), JobStatus::Cancelled);
+
+        app.execute_contract(
+            user2.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::UserUnstake {
+                amount: Uint128::new(50),
+                job_id,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            agent2.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::AgentUnstake {
+                amount: Uint128::new(20),
+                job_id,
+            },
+            &[],
+        )
+        .unwrap();
+        for addr in [user2.clone(), agent2.clone()] {
+            let balance: cw20::BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &cw20_addr,
+                    &Cw20QueryMsg::Balance {
+                        address: addr.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(balance.balance, Uint128::new(500));
+        }
+    }
+
+    #[test]
+    fn test_payout_authority() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+        let arbiter = app.api().addr_make("arbiter");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_id, 10);
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_id);
+
+        // a random address cannot trigger either payout
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByAgent { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByTime { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+
+        // agents of the job cannot pay themselves either
+        let err = app
+            .execute_contract(agent1.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByAgent { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+
+        // only the owner appoints the payout authority
+        let err = app
+            .execute_contract(
+                user2.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::SetPayoutAuthority {
+                    job_id,
+                    authority: Some(user2.clone()),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotJobOwner {}));
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::SetPayoutAuthority {
+                job_id,
+                authority: Some(arbiter.clone()),
+            },
+            &[],
+        )
+        .unwrap();
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id })
+            .unwrap();
+        assert_eq!(job.payout_authority, Some(arbiter.clone()));
+
+        // the arbiter can release a time unit payout
+        app.execute_contract(arbiter.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByTime { job_id }, &[])
+            .unwrap();
+        let user1_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::GetUserStake {
+                    user_addr: user1.clone(),
+                    job_id,
+                },
+            )
+            .unwrap();
+        assert_eq!(user1_stake.amount, Uint128::new(80));
+
+        // once revoked the arbiter is a random address again
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::SetPayoutAuthority {
+                job_id,
+                authority: None,
+            },
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(arbiter.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByAgent { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn test_user_stake_and_unstake() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        // set up cw20 contract
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        // set up agent work contract
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        // allocate 500 TTK to user1 and user2
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        // user1 create job 1
+        create_job(&mut app, agent_work_addr.clone(), user1.clone());
+
+        // user1 give allowance and stake 200 TTK
+        app.execute_contract(
+            user1.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(200),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        let response = app
+            .execute_contract(
+                user1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::UserStake {
+                    amount: Uint128::new(200),
+                    job_id: Uint128::new(1),
+                },
+                &[],
+            )
+            .unwrap();
+        assert!(response.events.iter().any(|e| e.ty == "wasm"
+            && e.attributes
+                .iter()
+                .any(|attr| attr.key == "action" && attr.value == "user stake")));
+
+        // check whether current user1 balance is 300 and user1 stake is 200
+        let user1_balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &cw20_addr,
+                &Cw20QueryMsg::Balance {
+                    address: user1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(user1_balance.balance, Uint128::new(300));
+        let user1_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::GetUserStake {
+                    user_addr: user1.clone(),
+                    job_id: Uint128::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(user1_stake.amount, Uint128::new(200));
+
+        // user1 unstake 100
+        let response = app
+            .execute_contract(
+                user1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::UserUnstake {
+                    amount: Uint128::new(100),
+                    job_id: Uint128::new(1),
+                },
+                &[],
+            )
+            .unwrap();
+        assert!(response.events.iter().any(|e| e.ty == "wasm"
+            && e.attributes
+                .iter()
+                .any(|attr| attr.key == "action" && attr.value == "user unstake")));
+
+        // check whether current user1 balance is 400 and user1 stake is 100
+        let user1_balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &cw20_addr,
+                &Cw20QueryMsg::Balance {
+                    address: user1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(user1_balance.balance, Uint128::new(400));
+        let user1_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::GetUserStake {
+                    user_addr: user1.clone(),
+                    job_id: Uint128::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(user1_stake.amount, Uint128::new(100));
+
+        // the escrow of a completed job stays locked while an agent is on it
+        let job_id = Uint128::new(1);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_id);
+        let err = app
+            .execute_contract(
+                user1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::UserUnstake { amount: Uint128::new(100), job_id },
+                &[],
+            )
+            .unwrap_err();
+        assert_invalid_job_status(err.downcast().unwrap(), JobStatus::Completed);
+
+        // once the last agent leaves before the payout, the owner takes the escrow back
+        app.execute_contract(
+            agent1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::AgentUnstake { amount: Uint128::new(10), job_id },
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByAgent { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NoAgents {}));
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::UserUnstake { amount: Uint128::new(100), job_id },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(query_balance(&app, &cw20_addr, &user1), Uint128::new(500));
+    }
+
+    #[test]
+    fn test_agent_stake_and_unstake() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        // set up cw20 contract
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        // set up agent work contract
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        // allocate 500 TTK to user1, user2, agent1, agent2, agent3
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        // user1 create job 1
+        create_job(&mut app, agent_work_addr.clone(), user1.clone());
+
+        // agent1 give allowance and stake 200 TTK
+        app.execute_contract(
+            agent1.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(200),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        hire_agent(&mut app, agent_work_addr.clone(), agent1.clone(), Uint128::new(1), 10);
+        let response = app
+            .execute_contract(
+                agent1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::AgentStake {
+                    amount: Uint128::new(200),
+                    job_id: Uint128::new(1),
+                    collateral_asset: None,
+                },
+                &[],
+            )
+            .unwrap();
+        assert!(response.events.iter().any(|e| e.ty == "wasm"
+            && e.attributes
+                .iter()
+                .any(|attr| attr.key == "action" && attr.value == "agent stake")));
+
+        // check whether current agent1 balance is 300 and agent1 stake is 200
+        let agent1_balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &cw20_addr,
+                &Cw20QueryMsg::Balance {
+                    address: agent1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(agent1_balance.balance, Uint128::new(300));
+        let agent1_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::GetAgentStake {
+                    agent_addr: agent1.clone(),
+                    job_id: Uint128::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(agent1_stake.amount, Uint128::new(200));
+
+        // agent1 unstake 100
+        let response = app
+            .execute_contract(
+                agent1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::AgentUnstake {
+                    amount: Uint128::new(100),
+                    job_id: Uint128::new(1),
+                },
+                &[],
+            )
+            .unwrap();
+        assert!(response.events.iter().any(|e| e.ty == "wasm"
+            && e.attributes
+                .iter()
+                .any(|attr| attr.key == "action" && attr.value == "agent unstake")));
+
+        // check whether current agent1 balance is 400 and agent1 stake is 100
+        let agent1_balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &cw20_addr,
+                &Cw20QueryMsg::Balance {
+                    address: agent1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(agent1_balance.balance, Uint128::new(400));
+        let agent1_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::GetAgentStake {
+                    agent_addr: agent1.clone(),
+                    job_id: Uint128::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(agent1_stake.amount, Uint128::new(100));
+    }
+
+    #[test]
+    fn test_distribute_rewards_by_agent() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        // set up cw20 contract
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        // set up agent work contract
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        // allocate 500 TTK to user1, user2, agent1, agent2, agent3
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        // user1 create job 1
+        create_job(&mut app, agent_work_addr.clone(), user1.clone());
+
+        // user1 give allowance and stake 100 TTK
+        app.execute_contract(
+            user1.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(100),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::UserStake {
+                amount: Uint128::new(100),
+                job_id: Uint128::new(1),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // agent1 give allowance and stake 10 TTK
+        app.execute_contract(
+            agent1.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(10),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        hire_agent(&mut app, agent_work_addr.clone(), agent1.clone(), Uint128::new(1), 10);
+        app.execute_contract(
+            agent1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::AgentStake {
+                amount: Uint128::new(10),
+                job_id: Uint128::new(1),
+                collateral_asset: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // agent2 give allowance and stake 10 TTK
+        app.execute_contract(
+            agent2.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(10),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        hire_agent(&mut app, agent_work_addr.clone(), agent2.clone(), Uint128::new(1), 10);
+        app.execute_contract(
+            agent2.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::AgentStake {
+                amount: Uint128::new(10),
+                job_id: Uint128::new(1),
+                collateral_asset: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // agent3 give allowance and stake 10 TTK
+        app.execute_contract(
+            agent3.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(10),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        hire_agent(&mut app, agent_work_addr.clone(), agent3.clone(), Uint128::new(1), 10);
+        app.execute_contract(
+            agent3.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::AgentStake {
+                amount: Uint128::new(10),
+                job_id: Uint128::new(1),
+                collateral_asset: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // job goes through its lifecycle before rewards can be paid
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), Uint128::new(1));
+
+        // distribute rewards
+        let response = app
+            .execute_contract(
+                user1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::DistributeRewardsByAgent {
+                    job_id: Uint128::new(1)
+                },
+                &[],
+            )
+            .unwrap();
+        assert!(response.events.iter().any(|e| e.ty == "wasm"
+            && e.attributes
+                .iter()
+                .any(|attr| attr.key == "action" && attr.value == "distribution rewards by agent")));
+        for agent in [&agent1, &agent2, &agent3] {
+            withdraw_all(&mut app, &agent_work_addr, agent);
+        }
+
+        // check whether current agent3 balance is 533 and agent1 stake is 0
+        let agent3_balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &cw20_addr,
+                &Cw20QueryMsg::Balance {
+                    address: agent3.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(agent3_balance.balance, Uint128::new(533));
+        let agent3_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::GetAgentStake {
+                    agent_addr: agent3.clone(),
+                    job_id: Uint128::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(agent3_stake.amount, Uint128::zero());
+    }
+
+    #[test]
+    fn test_distribute_rewards_by_time() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        // set up cw20 contract
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        // set up agent work contract
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        // allocate 500 TTK to user1, user2, agent1, agent2, agent3
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        // user1 create job 1
+        create_job(&mut app, agent_work_addr.clone(), user1.clone());
+
+        // user1 give allowance and stake 100 TTK
+        app.execute_contract(
+            user1.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(100),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::UserStake {
+                amount: Uint128::new(100),
+                job_id: Uint128::new(1),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // agent1 give allowance and stake 10 TTK
+        app.execute_contract(
+            agent1.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(10),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        hire_agent(&mut app, agent_work_addr.clone(), agent1.clone(), Uint128::new(1), 5);
+        app.execute_contract(
+            agent1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::AgentStake {
+                amount: Uint128::new(10),
+                job_id: Uint128::new(1),
+                collateral_asset: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // agent2 give allowance and stake 10 TTK
+        app.execute_contract(
+            agent2.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(10),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        hire_agent(&mut app, agent_work_addr.clone(), agent2.clone(), Uint128::new(1), 10);
+        app.execute_contract(
+            agent2.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::AgentStake {
+                amount: Uint128::new(10),
+                job_id: Uint128::new(1),
+                collateral_asset: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // job goes through its lifecycle before rewards can be paid
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), Uint128::new(1));
+
+        let response = app
+            .execute_contract(
+                user1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::DistributeRewardsByTime {
+                    job_id: Uint128::new(1)
+                },
+                &[],
+            )
+            .unwrap();
+        assert!(response.events.iter().any(|e| e.ty == "wasm"
+            && e.attributes
+                .iter()
+                .any(|attr| attr.key == "action" && attr.value == "distribution rewards by time unit")));
+        for agent in [&agent1, &agent2, &agent3] {
+            withdraw_all(&mut app, &agent_work_addr, agent);
+        }
+
+        // check whether current user1 balance is 400 and user1 stake is 85
+        let user1_balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &cw20_addr,
+                &Cw20QueryMsg::Balance {
+                    address: user1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(user1_balance.balance, Uint128::new(400));
+        let user1_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::GetUserStake {
+                    user_addr: user1.clone(),
+                    job_id: Uint128::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(user1_stake.amount, Uint128::new(85));
+
+        // check whether current agent1 balance is 495 and agent1 stake is 10
+        let agent1_balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &cw20_addr,
+                &Cw20QueryMsg::Balance {
+                    address: agent1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(agent1_balance.balance, Uint128::new(495));
+        let agent1_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::GetAgentStake {
+                    agent_addr: agent1.clone(),
+                    job_id: Uint128::new(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(agent1_stake.amount, Uint128::new(10));
+    }
+
+    #[test]
+    fn test_agent_rates_per_job() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        // agent1 is hired at 5 on job 1, then at 10 on job 2
+        let job_1 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_1, 100);
+        hire_agent(&mut app, agent_work_addr.clone(), agent1.clone(), job_1, 5);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_1, 10);
+        let job_2 = create_job(&mut app, agent_work_addr.clone(), user2.clone());
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_2, 10);
+
+        // each job keeps the rate its owner accepted
+        for (job_id, rate) in [(job_1, 5), (job_2, 10)] {
+            let rates: Vec<AgentRateResponse> = app
+                .wrap()
+                .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAgentRates { job_id })
+                .unwrap();
+            assert_eq!(rates, vec![AgentRateResponse { agent: agent1.clone(), cost_per_unit_time: Uint128::new(rate) }]);
+        }
+
+        // job 1 pays a time unit at its own rate
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_1);
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByTime { job_id: job_1 }, &[])
+            .unwrap();
+        let user1_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetUserStake { user_addr: user1.clone(), job_id: job_1 })
+            .unwrap();
+        assert_eq!(user1_stake.amount, Uint128::new(95));
+        withdraw_all(&mut app, &agent_work_addr, &agent1);
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(485));
+    }
+
+    #[test]
+    fn test_juror_vote() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        // set up cw20 contract
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        // set up agent work contract
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        // allocate 500 TTK to user1, user2, agent1, agent2, agent3
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        // user1 dispute the work agent1 submitted
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_id);
+
+        // jurors bond before the panel is drawn, agent1 is bonded too but is a party of the job
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), 100);
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user2.clone(), 300);
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent3.clone(), 100);
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), admin.clone(), 100);
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), 100);
+        open_dispute(&mut app, agent_work_addr.clone(), user1.clone(), job_id);
+
+        // parties of the job are never drawn, so they cannot vote on their own dispute
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id })
+            .unwrap();
+        assert_eq!(dispute.panel.len(), 4);
+        assert!(!dispute.panel.contains(&agent1));
+        let err = commit_vote(&mut app, agent_work_addr.clone(), agent1.clone(), job_id, true).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotPanelMember {}));
+
+        // Agent 2 and User 2 commit accept, Agent 3 and admin commit reject
+        commit_vote(&mut app, agent_work_addr.clone(), agent2.clone(), job_id, true).unwrap();
+        commit_vote(&mut app, agent_work_addr.clone(), user2.clone(), job_id, true).unwrap();
+        commit_vote(&mut app, agent_work_addr.clone(), agent3.clone(), job_id, false).unwrap();
+        commit_vote(&mut app, agent_work_addr.clone(), admin.clone(), job_id, false).unwrap();
+
+        // a juror commits once per dispute
+        let err = commit_vote(&mut app, agent_work_addr.clone(), agent3.clone(), job_id, true).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AlreadyVoted {}));
+
+        // nothing is revealed during the commit period
+        let err = reveal_vote(&mut app, agent_work_addr.clone(), agent2.clone(), job_id, true).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotRevealPeriod {}));
+        let vote_result: VoteResultResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetVoteResult { job_id })
+            .unwrap();
+        assert_eq!(vote_result.accept_vote + vote_result.reject_vote, Uint128::zero());
+
+        start_reveal_period(&mut app);
+
+        // commits are closed once the reveal period starts
+        let err = commit_vote(&mut app, agent_work_addr.clone(), user1.clone(), job_id, true).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::VotingClosed {}));
+
+        // a reveal must match the commitment
+        let err = reveal_vote(&mut app, agent_work_addr.clone(), agent3.clone(), job_id, true).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidReveal {}));
+        let err = app
+            .execute_contract(
+                agent3.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::RevealVote {
+                    job_id,
+                    is_accept: false,
+                    salt: "wrong salt".to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidReveal {}));
+
+        reveal_vote(&mut app, agent_work_addr.clone(), agent2.clone(), job_id, true).unwrap();
+        reveal_vote(&mut app, agent_work_addr.clone(), user2.clone(), job_id, true).unwrap();
+        reveal_vote(&mut app, agent_work_addr.clone(), agent3.clone(), job_id, false).unwrap();
+        let err = reveal_vote(&mut app, agent_work_addr.clone(), agent3.clone(), job_id, false).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NoCommitFound {}));
+
+        // reveals are closed after the reveal period, the admin's commit is forfeited
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(DisputeConfig::default().reveal_period)
+        });
+        let err = reveal_vote(&mut app, agent_work_addr.clone(), admin.clone(), job_id, false).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotRevealPeriod {}));
+
+        let vote_result: VoteResultResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetVoteResult { job_id })
+            .unwrap();
+
+        assert_eq!(vote_result.accept_vote, Uint128::new(2));
+        assert_eq!(vote_result.reject_vote, Uint128::new(1));
+        assert_eq!(vote_result.accept_weight, Uint128::new(400));
+        assert_eq!(vote_result.reject_weight, Uint128::new(100));
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id })
+            .unwrap();
+        assert_eq!(dispute.commits, Uint128::new(4));
+    }
+
+    #[test]
+    fn test_dispute_records() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent3.clone(), 100);
+
+        // two disputed jobs, opened by the owner and by the agent
+        let job_1 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_1, 10);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_1);
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::OpenDispute {
+                job_id: job_1,
+                reason: "work is incomplete".to_string(),
+                seed_hash: seed_commitment("seed"),
+            },
+            &[],
+        )
+        .unwrap();
+        complete_draw(&mut app, agent_work_addr.clone(), user1.clone(), job_1);
+
+        let job_2 = create_job(&mut app, agent_work_addr.clone(), user2.clone());
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_2, 10);
+        submit_job(&mut app, agent_work_addr.clone(), user2.clone(), agent2.clone(), job_2);
+        app.execute_contract(
+            agent2.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::OpenDispute {
+                job_id: job_2,
+                reason: "owner is unresponsive".to_string(),
+                seed_hash: seed_commitment("seed"),
+            },
+            &[],
+        )
+        .unwrap();
+        complete_draw(&mut app, agent_work_addr.clone(), agent2.clone(), job_2);
+
+        // each dispute keeps its own tally
+        commit_vote(&mut app, agent_work_addr.clone(), agent3.clone(), job_1, false).unwrap();
+        commit_vote(&mut app, agent_work_addr.clone(), agent3.clone(), job_2, true).unwrap();
+        start_reveal_period(&mut app);
+        reveal_vote(&mut app, agent_work_addr.clone(), agent3.clone(), job_1, false).unwrap();
+        reveal_vote(&mut app, agent_work_addr.clone(), agent3.clone(), job_2, true).unwrap();
+
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id: job_2 })
+            .unwrap();
+        assert_eq!(dispute.opener, agent2);
+        assert_eq!(dispute.reason, "owner is unresponsive");
+        assert_eq!(dispute.accept_votes, Uint128::one());
+        assert_eq!(dispute.reject_votes, Uint128::zero());
+        assert_eq!(
+            dispute.commit_deadline,
+            dispute.opened_at.plus_seconds(DisputeConfig::default().commit_period)
+        );
+        assert_eq!(
+            dispute.reveal_deadline,
+            dispute.commit_deadline.plus_seconds(DisputeConfig::default().reveal_period)
+        );
+        assert_eq!(dispute.outcome, None);
+
+        let list: ListDisputesResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::ListDisputes {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            list.disputes.iter().map(|d| d.job_id).collect::<Vec<_>>(),
+            vec![job_1, job_2]
+        );
+        assert_eq!(list.disputes[0].reject_votes, Uint128::one());
+
+        let list: ListDisputesResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::ListDisputes {
+                    start_after: Some(job_1),
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+        assert_eq!(list.disputes.len(), 1);
+        assert_eq!(list.disputes[0].job_id, job_2);
+
+        // a job that was never disputed has no record to vote on
+        let job_3 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        let err = commit_vote(&mut app, agent_work_addr.clone(), agent3.clone(), job_3, true).unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::DisputeNotFound { .. }
+        ));
+    }
+
+    // stands in for a randomness beacon contract
+    fn beacon_instantiate(_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn beacon_execute(_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn beacon_query(_deps: Deps, _env: Env, _msg: BeaconQueryMsg) -> StdResult<Binary> {
+        to_json_binary(&RandomnessResponse {
+            randomness: Binary::from(b"beacon randomness".to_vec()),
+        })
+    }
+
+    #[test]
+    fn test_panel_selection() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        // agent1 is a party of the job and agent2 no longer has a bond, neither can be drawn
+        for juror in [user2.clone(), agent3.clone(), admin.clone(), agent1.clone(), agent2.clone()] {
+            register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), juror, 100);
+        }
+        app.execute_contract(agent2.clone(), agent_work_addr.clone(), &ExecuteMsg::UnregisterJuror {}, &[])
+            .unwrap();
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_id);
+
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::OpenDispute {
+                job_id,
+                reason: "work is incomplete".to_string(),
+                seed_hash: seed_commitment("seed"),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // nobody votes before the panel is drawn
+        let err = commit_vote(&mut app, agent_work_addr.clone(), user2.clone(), job_id, true).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotPanelMember {}));
+
+        // the second seed comes from the other side, and nothing is revealed before it is committed
+        let err = commit_seed(&mut app, agent_work_addr.clone(), user1.clone(), job_id).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        let err = reveal_seed(&mut app, agent_work_addr.clone(), user1.clone(), job_id, "seed").unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotSeedRevealPeriod {}));
+        let err = app
+            .execute_contract(
+                agent1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::CommitSeed { job_id, seed_hash: seed_commitment("seed") },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSeed {}));
+        commit_seed(&mut app, agent_work_addr.clone(), agent1.clone(), job_id).unwrap();
+        let err = commit_seed(&mut app, agent_work_addr.clone(), agent1.clone(), job_id).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::SeedAlreadyCommitted {}));
+
+        // parties reveal the committed seeds, the second reveal draws the panel
+        let err = reveal_seed(&mut app, agent_work_addr.clone(), user2.clone(), job_id, "seed").unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        let err = reveal_seed(&mut app, agent_work_addr.clone(), user1.clone(), job_id, "another seed").unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSeed {}));
+        reveal_seed(&mut app, agent_work_addr.clone(), user1.clone(), job_id, "seed").unwrap();
+        let err = reveal_seed(&mut app, agent_work_addr.clone(), user1.clone(), job_id, "seed").unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::SeedAlreadyRevealed {}));
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id })
+            .unwrap();
+        assert!(dispute.panel.is_empty());
+        assert_eq!(dispute.draw.unwrap().first_seed, Some("seed".to_string()));
+        reveal_seed(&mut app, agent_work_addr.clone(), agent1.clone(), job_id, "other seed").unwrap();
+        let err = reveal_seed(&mut app, agent_work_addr.clone(), agent1.clone(), job_id, "other seed").unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::PanelAlreadyDrawn {}));
+
+        // fewer eligible jurors than the panel size: all of them are drawn
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id })
+            .unwrap();
+        assert!(dispute.draw.is_none());
+        let mut panel = dispute.panel;
+        panel.sort();
+        let mut expected = vec![user2.clone(), agent3.clone(), admin.clone()];
+        expected.sort();
+        assert_eq!(panel, expected);
+        commit_vote(&mut app, agent_work_addr.clone(), user2.clone(), job_id, true).unwrap();
+
+        // with a beacon, its randomness is read once both seeds are committed
+        let beacon_code_id = app.store_code(Box::new(ContractWrapper::new(beacon_execute, beacon_instantiate, beacon_query)));
+        let beacon_addr = app
+            .instantiate_contract(beacon_code_id, admin.clone(), &Empty {}, &[], "Beacon", None)
+            .unwrap();
+        let beacon_work_addr = setup_agent_work_contract_with_config(
+            &mut app,
+            admin.clone(),
+            Asset::Cw20 { addr: cw20_addr.clone() },
+            Some(DisputeConfig {
+                panel_size: 2,
+                quorum: 1,
+                randomness_beacon: Some(beacon_addr),
+                ..DisputeConfig::default()
+            }),
+        );
+        let job_id = create_job(&mut app, beacon_work_addr.clone(), user1.clone());
+        agent_stake(&mut app, cw20_addr.clone(), beacon_work_addr.clone(), agent1.clone(), job_id, 10);
+        submit_job(&mut app, beacon_work_addr.clone(), user1.clone(), agent1.clone(), job_id);
+        let open_msg = ExecuteMsg::OpenDispute {
+            job_id,
+            reason: "work is incomplete".to_string(),
+            seed_hash: seed_commitment("seed"),
+        };
+        let err = app
+            .execute_contract(user1.clone(), beacon_work_addr.clone(), &open_msg, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NoEligibleJurors {}));
+
+        for juror in [user2.clone(), agent3.clone(), admin.clone()] {
+            register_juror(&mut app, cw20_addr.clone(), beacon_work_addr.clone(), juror, 100);
+        }
+        app.execute_contract(user1.clone(), beacon_work_addr.clone(), &open_msg, &[])
+            .unwrap();
+        commit_seed(&mut app, beacon_work_addr.clone(), agent1.clone(), job_id).unwrap();
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&beacon_work_addr, &QueryMsg::GetDispute { job_id })
+            .unwrap();
+        assert_eq!(dispute.draw.unwrap().beacon, Some(Binary::from(b"beacon randomness".to_vec())));
+        reveal_seed(&mut app, beacon_work_addr.clone(), agent1.clone(), job_id, "other seed").unwrap();
+        reveal_seed(&mut app, beacon_work_addr.clone(), user1.clone(), job_id, "seed").unwrap();
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&beacon_work_addr, &QueryMsg::GetDispute { job_id })
+            .unwrap();
+        assert_eq!(dispute.panel.len(), 2);
+        assert_ne!(dispute.panel[0], dispute.panel[1]);
+        assert!(dispute.panel.iter().all(|juror| [&user2, &agent3, &admin].contains(&juror)));
+    }
+
+    #[test]
+    fn test_panel_draw_missed() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+        for juror in [agent3.clone(), admin.clone(), user2.clone()] {
+            register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), juror, 100);
+        }
+        let open_msg = |job_id| ExecuteMsg::OpenDispute {
+            job_id,
+            reason: "work is incomplete".to_string(),
+            seed_hash: seed_commitment("seed"),
+        };
+        let dispute_config = DisputeConfig::default();
+
+        // the agents never commit their seed: they lose and the owner is refunded
+        let job_1 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_1, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_1, 10);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_1);
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &open_msg(job_1), &[]).unwrap();
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id: job_1 }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::VotingNotFinished {}));
+        app.update_block(|block| block.time = block.time.plus_seconds(dispute_config.commit_period));
+        let err = commit_seed(&mut app, agent_work_addr.clone(), agent1.clone(), job_1).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::VotingClosed {}));
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id: job_1 }, &[])
+            .unwrap();
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id: job_1 })
+            .unwrap();
+        assert_eq!(dispute.outcome, Some(DisputeOutcome::Rejected));
+        assert!(dispute.rounds[0].panel.is_empty());
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id: job_1 })
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Cancelled);
+
+        // the owner opens and never reveals its seed: the agents are paid
+        let job_2 = create_job(&mut app, agent_work_addr.clone(), user2.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user2.clone(), job_2, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_2, 10);
+        submit_job(&mut app, agent_work_addr.clone(), user2.clone(), agent2.clone(), job_2);
+        app.execute_contract(user2.clone(), agent_work_addr.clone(), &open_msg(job_2), &[]).unwrap();
+        commit_seed(&mut app, agent_work_addr.clone(), agent2.clone(), job_2).unwrap();
+        reveal_seed(&mut app, agent_work_addr.clone(), agent2.clone(), job_2, "other seed").unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(dispute_config.reveal_period));
+        let err = reveal_seed(&mut app, agent_work_addr.clone(), user2.clone(), job_2, "seed").unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotSeedRevealPeriod {}));
+        app.execute_contract(agent2.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id: job_2 }, &[])
+            .unwrap();
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id: job_2 })
+            .unwrap();
+        assert_eq!(dispute.outcome, Some(DisputeOutcome::Accepted));
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id: job_2 })
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+
+        // the agent appeals a rejection and never reveals its seed: the rejection stands
+        // and the appeal bond goes to the jurors who decided it
+        let job_3 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_3, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_3, 10);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_3);
+        open_dispute(&mut app, agent_work_addr.clone(), user1.clone(), job_3);
+        let votes = [(agent3.clone(), false), (admin.clone(), false), (user2.clone(), false)];
+        vote_round(&mut app, agent_work_addr.clone(), job_3, &votes);
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id: job_3 }, &[])
+            .unwrap();
+        app.execute_contract(
+            agent1.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(100),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            agent1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::Appeal { job_id: job_3, seed_hash: seed_commitment("seed") },
+            &[],
+        )
+        .unwrap();
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id: job_3 })
+            .unwrap();
+        assert!(dispute.panel.is_empty());
+        assert_eq!(dispute.draw.unwrap().first_party, agent1);
+        commit_seed(&mut app, agent_work_addr.clone(), user1.clone(), job_3).unwrap();
+        reveal_seed(&mut app, agent_work_addr.clone(), user1.clone(), job_3, "other seed").unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(dispute_config.reveal_period));
+        let rewards_before: Vec<Uint128> = votes
+            .iter()
+            .map(|(juror, _)| query_claimable(&app, &agent_work_addr, juror.clone()))
+            .collect();
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id: job_3 }, &[])
+            .unwrap();
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id: job_3 })
+            .unwrap();
+        assert_eq!(dispute.outcome, Some(DisputeOutcome::Rejected));
+        assert_eq!(dispute.rounds.len(), 2);
+        let bond_rewards: Uint128 = votes
+            .iter()
+            .zip(rewards_before)
+            .map(|((juror, _), before)| query_claimable(&app, &agent_work_addr, juror.clone()) - before)
+            .sum();
+        assert_eq!(bond_rewards, Uint128::new(100));
+    }
+
+    #[test]
+    fn test_juror_registry() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract_with_config(
+            &mut app,
+            admin.clone(),
+            Asset::Cw20 { addr: cw20_addr.clone() },
+            Some(DisputeConfig { max_jurors: 3, ..DisputeConfig::default() }),
+        );
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        // a bond below the configured minimum is rejected
+        app.execute_contract(
+            user2.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(50),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                user2.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::RegisterJuror {
+                    amount: Uint128::new(50),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::JurorBondTooLow { .. }
+        ));
+
+        // user2 bonds 100 then tops up 50, agent3 bonds 200
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user2.clone(), 100);
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user2.clone(), 50);
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent3.clone(), 200);
+
+        let juror: JurorResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::GetJuror {
+                    juror_addr: user2.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(juror.bonded, Uint128::new(150));
+        assert_eq!(juror.unbonding, Uint128::zero());
+        let list: ListJurorsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::ListJurors {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(list.jurors.len(), 2);
+
+        // the registry takes 3 jurors, a top up of a registered one still goes through
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), 100);
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), 10);
+        let register_agent1 = |app: &mut App| {
+            app.execute_contract(
+                agent1.clone(),
+                cw20_addr.clone(),
+                &Cw20ExecuteMsg::Send {
+                    contract: agent_work_addr.to_string(),
+                    amount: Uint128::new(100),
+                    msg: to_json_binary(&ReceiveMsg::BondJuror {}).unwrap(),
+                },
+                &[],
+            )
+        };
+        let err = register_agent1(&mut app).unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::JurorRegistryFull { max_jurors: 3 }
+        ));
+
+        // unregistering starts the unbonding period
+        app.execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::UnregisterJuror {}, &[])
+            .unwrap();
+        let juror: JurorResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::GetJuror {
+                    juror_addr: user2.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(juror.bonded, Uint128::zero());
+        assert_eq!(juror.unbonding, Uint128::new(150));
+        // an unbonding juror keeps its place in the registry
+        let err = register_agent1(&mut app).unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::JurorRegistryFull { max_jurors: 3 }
+        ));
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::WithdrawJurorBond {}, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::UnbondingNotFinished {}
+        ));
+
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(DisputeConfig::default().juror_unbonding_period)
+        });
+        app.execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::WithdrawJurorBond {}, &[])
+            .unwrap();
+        let user2_balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &cw20_addr,
+                &Cw20QueryMsg::Balance {
+                    address: user2.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(user2_balance.balance, Uint128::new(500));
+
+        // the juror record is gone once fully withdrawn
+        let res: StdResult<JurorResponse> = app.wrap().query_wasm_smart(
+            &agent_work_addr,
+            &QueryMsg::GetJuror {
+                juror_addr: user2.clone(),
+            },
+        );
+        assert!(res.is_err());
+        let list: ListJurorsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &agent_work_addr,
+                &QueryMsg::ListJurors {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(list.jurors.len(), 2);
+        assert!(list.jurors.iter().all(|juror| juror.address != user2));
+
+        // the withdrawn place is free again
+        register_agent1(&mut app).unwrap();
+    }
+
+    #[test]
+    fn test_vote_weighting() {
+        let mut dispute_config = DisputeConfig::default();
+        assert_eq!(dispute_config.vote_weight(Uint128::new(400)), Uint128::new(400));
+
+        dispute_config.vote_weighting = VoteWeighting::SquareRoot;
+        assert_eq!(dispute_config.vote_weight(Uint128::new(400)), Uint128::new(20));
+        assert_eq!(dispute_config.vote_weight(Uint128::new(150)), Uint128::new(12));
+
+        // the cap applies on top of the weighting
+        dispute_config.max_vote_weight = Some(Uint128::new(15));
+        assert_eq!(dispute_config.vote_weight(Uint128::new(400)), Uint128::new(15));
+        assert_eq!(dispute_config.vote_weight(Uint128::new(100)), Uint128::new(10));
+
+        dispute_config.vote_weighting = VoteWeighting::Linear;
+        assert_eq!(dispute_config.vote_weight(Uint128::new(400)), Uint128::new(15));
+    }
+
+    fn query_balance(app: &App, cw20_addr: &Addr, addr: &Addr) -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                cw20_addr,
+                &Cw20QueryMsg::Balance {
+                    address: addr.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    }
+
+    // rewards of every asset summed, the tests only pay jurors in one
+    // sum of every balance credited to `addr`, juror rewards included
+    fn query_claimable(app: &App, agent_work_addr: &Addr, addr: Addr) -> Uint128 {
+        let claimable: Vec<AssetAmount> = app
+            .wrap()
+            .query_wasm_smart(agent_work_addr, &QueryMsg::GetClaimable { addr })
+            .unwrap();
+        claimable.iter().map(|claimable| claimable.amount).sum()
+    }
+
+    // withdraw every balance credited to `addr` by payouts
+    fn withdraw_all(app: &mut App, agent_work_addr: &Addr, addr: &Addr) {
+        let claimable: Vec<AssetAmount> = app
+            .wrap()
+            .query_wasm_smart(agent_work_addr, &QueryMsg::GetClaimable { addr: addr.clone() })
+            .unwrap();
+        for AssetAmount { asset, amount } in claimable {
+            app.execute_contract(
+                addr.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::Withdraw { asset, amount },
+                &[],
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_finalize_dispute_accepted() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        let juror4 = app.api().addr_make("juror4");
+        app.execute_contract(
+            admin.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: juror4.to_string(),
+                amount: Uint128::new(500),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 120);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_id, 10);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_id);
+        for juror in [user2.clone(), agent3.clone(), admin.clone(), juror4.clone()] {
+            register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), juror, 100);
+        }
+        open_dispute(&mut app, agent_work_addr.clone(), user1.clone(), job_id);
+
+        // two jurors accept the work, one rejects it and juror4 never reveals
+        for (juror, is_accept) in [
+            (user2.clone(), true),
+            (agent3.clone(), true),
+            (admin.clone(), false),
+            (juror4.clone(), true),
+        ] {
+            commit_vote(&mut app, agent_work_addr.clone(), juror, job_id, is_accept).unwrap();
+        }
+        start_reveal_period(&mut app);
+        for (juror, is_accept) in [(user2.clone(), true), (agent3.clone(), true), (admin.clone(), false)] {
+            reveal_vote(&mut app, agent_work_addr.clone(), juror, job_id, is_accept).unwrap();
+        }
+
+        // the dispute cannot be settled while votes can still be revealed
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::VotingNotFinished {}));
+
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(DisputeConfig::default().reveal_period)
+        });
+        let response = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id }, &[])
+            .unwrap();
+        assert!(response.events.iter().any(|e| e.ty == "wasm-dispute_round"
+            && e.attributes
+                .iter()
+                .any(|attr| attr.key == "outcome" && attr.value == "accepted")));
+
+        // the escrow stays locked while the owner may still appeal
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(490));
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AppealPeriodNotFinished {}));
+        end_appeal_period(&mut app);
+        let response = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id }, &[])
+            .unwrap();
+        assert!(response.events.iter().any(|e| e.ty == "wasm-dispute_outcome"
+            && e.attributes
+                .iter()
+                .any(|attr| attr.key == "outcome" && attr.value == "accepted")));
+
+        // 5% of the escrow is the dispute fee, the rest is split between the agents
+        // and their stake is repaid
+        for agent in [&agent1, &agent2] {
+            withdraw_all(&mut app, &agent_work_addr, agent);
+        }
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(557));
+        assert_eq!(query_balance(&app, &cw20_addr, &agent2), Uint128::new(557));
+        assert_eq!(query_balance(&app, &cw20_addr, &user1), Uint128::new(380));
+
+        // the minority juror and the juror who did not reveal lose 10% of their bond
+        for juror in [admin.clone(), juror4.clone()] {
+            let juror: JurorResponse = app
+                .wrap()
+                .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJuror { juror_addr: juror })
+                .unwrap();
+            assert_eq!(juror.bonded, Uint128::new(90));
+        }
+
+        // fee and slashed bonds are shared by the coherent jurors: (6 + 10 + 10) / 2
+        for juror in [user2.clone(), agent3.clone()] {
+            let rewards = query_claimable(&app, &agent_work_addr, juror);
+            assert_eq!(rewards, Uint128::new(13));
+        }
+        let rewards = query_claimable(&app, &agent_work_addr, admin.clone());
+        assert_eq!(rewards, Uint128::zero());
+
+        // rewards are withdrawn like any other payout, once
+        withdraw_all(&mut app, &agent_work_addr, &user2);
+        assert_eq!(query_balance(&app, &cw20_addr, &user2), Uint128::new(413));
+        let err = app
+            .execute_contract(
+                user2.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::Withdraw { asset: Asset::Cw20 { addr: cw20_addr.clone() }, amount: Uint128::one() },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NothingToWithdraw {}));
+
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id })
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id })
+            .unwrap();
+        assert_eq!(dispute.outcome, Some(DisputeOutcome::Accepted));
+
+        // the escrow is released once
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::DisputeFinalized {}));
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::DistributeRewardsByAgent { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NothingToDistribute {}));
+    }
+
+    #[test]
+    fn test_finalize_dispute_rejected_and_no_quorum() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        for juror in [user2.clone(), agent3.clone(), admin.clone()] {
+            register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), juror, 100);
+        }
+
+        // job 1: user1 with agent1 and agent2, job 2: user2 with agent3
+        let job_1 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_1, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_1, 20);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_1, 20);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_1);
+        open_dispute(&mut app, agent_work_addr.clone(), user1.clone(), job_1);
+
+        let job_2 = create_job(&mut app, agent_work_addr.clone(), user2.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user2.clone(), job_2, 50);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent3.clone(), job_2, 20);
+        submit_job(&mut app, agent_work_addr.clone(), user2.clone(), agent3.clone(), job_2);
+        open_dispute(&mut app, agent_work_addr.clone(), agent3.clone(), job_2);
+
+        // every juror rejects job 1, only the admin votes on job 2
+        for juror in [user2.clone(), agent3.clone(), admin.clone()] {
+            commit_vote(&mut app, agent_work_addr.clone(), juror, job_1, false).unwrap();
+        }
+        commit_vote(&mut app, agent_work_addr.clone(), admin.clone(), job_2, true).unwrap();
+        start_reveal_period(&mut app);
+        for juror in [user2.clone(), agent3.clone(), admin.clone()] {
+            reveal_vote(&mut app, agent_work_addr.clone(), juror, job_1, false).unwrap();
+        }
+        reveal_vote(&mut app, agent_work_addr.clone(), admin.clone(), job_2, true).unwrap();
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(DisputeConfig::default().reveal_period)
+        });
+
+        // rejected: owner is refunded and receives 10% of each agent stake,
+        // 5% of each agent stake is the dispute fee. Funds are credited once the appeal period is over
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id: job_1 }, &[])
+            .unwrap();
+        assert_eq!(query_balance(&app, &cw20_addr, &user1), Uint128::new(400));
+        end_appeal_period(&mut app);
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id: job_1 }, &[])
+            .unwrap();
+        for addr in [&user1, &agent1, &agent2] {
+            withdraw_all(&mut app, &agent_work_addr, addr);
+        }
+        assert_eq!(query_balance(&app, &cw20_addr, &user1), Uint128::new(504));
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(497));
+        assert_eq!(query_balance(&app, &cw20_addr, &agent2), Uint128::new(497));
+        let mut total_rewards = Uint128::zero();
+        for juror in [user2.clone(), agent3.clone(), admin.clone()] {
+            let rewards = query_claimable(&app, &agent_work_addr, juror);
+            total_rewards += rewards;
+        }
+        assert_eq!(total_rewards, Uint128::new(2));
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id: job_1 })
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Cancelled);
+        // the refunded agents are off the job
+        let num_of_agent: Uint128 = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetNumOfAgent { job_id: job_1 })
+            .unwrap();
+        assert_eq!(num_of_agent, Uint128::zero());
+
+        // no quorum: everybody is refunded without slashing, there is nothing to appeal
+        let response = app
+            .execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id: job_2 }, &[])
+            .unwrap();
+        assert!(response.events.iter().any(|e| e.ty == "wasm-dispute_outcome"
+            && e.attributes
+                .iter()
+                .any(|attr| attr.key == "outcome" && attr.value == "no_quorum")));
+        for addr in [&user2, &agent3] {
+            withdraw_all(&mut app, &agent_work_addr, addr);
+        }
+        // user2 and agent3 also bonded 100 as jurors
+        assert_eq!(query_balance(&app, &cw20_addr, &user2), Uint128::new(400));
+        assert_eq!(query_balance(&app, &cw20_addr, &agent3), Uint128::new(400));
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id: job_2 })
+            .unwrap();
+        assert_eq!(dispute.outcome, Some(DisputeOutcome::NoQuorum));
+
+        // a panel that stays silent is slashed all the same, the slashed bonds wait for the
+        // coherent jurors of the next decided round
+        let jurors = [user2.clone(), agent3.clone(), admin.clone()];
+        let claimable_before: Vec<Uint128> = jurors.iter().map(|juror| query_claimable(&app, &agent_work_addr, juror.clone())).collect();
+        let job_3 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_3, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_3, 20);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_3);
+        open_dispute(&mut app, agent_work_addr.clone(), user1.clone(), job_3);
+        start_reveal_period(&mut app);
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(DisputeConfig::default().reveal_period)
+        });
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id: job_3 }, &[])
+            .unwrap();
+        for juror in &jurors {
+            let juror: JurorResponse = app
+                .wrap()
+                .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJuror { juror_addr: juror.clone() })
+                .unwrap();
+            assert_eq!(juror.bonded, Uint128::new(90));
+        }
+        for juror in &jurors {
+            register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), juror.clone(), 10);
+        }
+        let job_4 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_4, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_4, 20);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent2.clone(), job_4);
+        open_dispute(&mut app, agent_work_addr.clone(), user1.clone(), job_4);
+        vote_round(&mut app, agent_work_addr.clone(), job_4, &[(user2.clone(), false), (agent3.clone(), false), (admin.clone(), false)]);
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id: job_4 }, &[])
+            .unwrap();
+        let rewarded: Uint128 = jurors
+            .iter()
+            .zip(claimable_before)
+            .map(|(juror, before)| query_claimable(&app, &agent_work_addr, juror.clone()) - before)
+            .sum();
+        assert_eq!(rewarded, Uint128::new(30));
+    }
+
+    fn appeal(app: &mut App, cw20_addr: Addr, agent_work_addr: Addr, appellant: Addr, job_id: Uint128, bond: u128) -> AnyResult<AppResponse> {
+        app.execute_contract(
+            appellant.clone(),
+            cw20_addr,
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(bond),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        let response = app.execute_contract(
+            appellant.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::Appeal { job_id, seed_hash: seed_commitment("seed") },
+            &[],
+        )?;
+        complete_draw(app, agent_work_addr, appellant, job_id);
+        Ok(response)
+    }
+
+    // commit and reveal the same vote for every juror, then close the reveal period
+    fn vote_round(app: &mut App, agent_work_addr: Addr, job_id: Uint128, votes: &[(Addr, bool)]) {
+        for (juror, is_accept) in votes {
+            commit_vote(app, agent_work_addr.clone(), juror.clone(), job_id, *is_accept).unwrap();
+        }
+        start_reveal_period(app);
+        for (juror, is_accept) in votes {
+            reveal_vote(app, agent_work_addr.clone(), juror.clone(), job_id, *is_accept).unwrap();
+        }
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(DisputeConfig::default().reveal_period)
+        });
+    }
+
+    #[test]
+    fn test_dispute_appeal() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        // agent2 is only a juror here
+        for juror in [user2.clone(), agent3.clone(), admin.clone(), agent2.clone()] {
+            register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), juror, 100);
+        }
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_id);
+        open_dispute(&mut app, agent_work_addr.clone(), user1.clone(), job_id);
+
+        // round 0 rejects the work, agent2 is slashed below the minimum bond
+        vote_round(
+            &mut app,
+            agent_work_addr.clone(),
+            job_id,
+            &[(user2.clone(), false), (agent3.clone(), false), (admin.clone(), false), (agent2.clone(), true)],
+        );
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id }, &[])
+            .unwrap();
+
+        // only the losing side appeals, paying the first appeal bond
+        let err = appeal(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 100).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        appeal(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 100).unwrap();
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(390));
+        let err = appeal(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 100).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotAppealable {}));
+        let err = app
+            .execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::VotingNotFinished {}));
+
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id })
+            .unwrap();
+        assert_eq!(dispute.round, 1);
+        assert_eq!(dispute.rounds.len(), 1);
+        assert_eq!(dispute.rounds[0].outcome, DisputeOutcome::Rejected);
+        assert_eq!(dispute.rounds[0].appellant, Some(agent1.clone()));
+        assert_eq!(dispute.rounds[0].appeal_bond, Uint128::new(100));
+        // the panel doubles but agent2 no longer has the minimum bond
+        assert_eq!(dispute.panel.len(), 3);
+        assert!(!dispute.panel.contains(&agent2));
+
+        // round 1 overturns the outcome, the appeal bond is credited back
+        let votes = [(user2.clone(), true), (agent3.clone(), true), (admin.clone(), true)];
+        vote_round(&mut app, agent_work_addr.clone(), job_id, &votes);
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id }, &[])
+            .unwrap();
+        withdraw_all(&mut app, &agent_work_addr, &agent1);
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(490));
+
+        // the owner appeals in turn with a doubled bond, round 2 confirms and is final
+        appeal(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 200).unwrap();
+        vote_round(&mut app, agent_work_addr.clone(), job_id, &votes);
+        let response = app
+            .execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::FinalizeDispute { job_id }, &[])
+            .unwrap();
+        assert!(response.events.iter().any(|e| e.ty == "wasm-dispute_outcome"
+            && e.attributes
+                .iter()
+                .any(|attr| attr.key == "outcome" && attr.value == "accepted")));
+
+        // agents get the escrow minus the 5% dispute fee, the owner loses the bond
+        for addr in [&agent1, &user1] {
+            withdraw_all(&mut app, &agent_work_addr, addr);
+        }
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(595));
+        assert_eq!(query_balance(&app, &cw20_addr, &user1), Uint128::new(200));
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id })
+            .unwrap();
+        assert_eq!(dispute.rounds.len(), 3);
+        assert_eq!(dispute.rounds[1].appeal_bond, Uint128::new(200));
+        assert_eq!(dispute.outcome, Some(DisputeOutcome::Accepted));
+        assert_eq!(dispute.appeal_deadline, None);
+
+        // jurors shared agent2's slashed bond, the forfeited appeal bond and the dispute fee
+        let mut total_rewards = Uint128::zero();
+        for juror in [user2.clone(), agent3.clone(), admin.clone()] {
+            let rewards = query_claimable(&app, &agent_work_addr, juror);
+            total_rewards += rewards;
+        }
+        assert_eq!(total_rewards, Uint128::new(215));
+    }
+
+    #[test]
+    fn test_receive_hooks() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+
+        // a single Send moves the tokens and stakes them, no allowance needed
+        let send = |amount: u128, msg: &ReceiveMsg| Cw20ExecuteMsg::Send {
+            contract: agent_work_addr.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_json_binary(msg).unwrap(),
+        };
+        app.execute_contract(user1.clone(), cw20_addr.clone(), &send(100, &ReceiveMsg::FundJob { job_id }), &[])
+            .unwrap();
+        hire_agent(&mut app, agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        app.execute_contract(
+            agent1.clone(),
+            cw20_addr.clone(),
+            &send(
+                10,
+                &ReceiveMsg::AgentStake {
+                    job_id,
+                },
+            ),
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(user2.clone(), cw20_addr.clone(), &send(150, &ReceiveMsg::BondJuror {}), &[])
+            .unwrap();
+
+        let user_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetUserStake { user_addr: user1.clone(), job_id })
+            .unwrap();
+        assert_eq!(user_stake.amount, Uint128::new(100));
+        let agent_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAgentStake { agent_addr: agent1.clone(), job_id })
+            .unwrap();
+        assert_eq!(agent_stake.amount, Uint128::new(10));
+        let juror: JurorResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJuror { juror_addr: user2.clone() })
+            .unwrap();
+        assert_eq!(juror.bonded, Uint128::new(150));
+        assert_eq!(query_balance(&app, &cw20_addr, &agent_work_addr), Uint128::new(260));
+
+        // the hooks apply the same checks as the direct messages
+        let err = app
+            .execute_contract(user2.clone(), cw20_addr.clone(), &send(100, &ReceiveMsg::FundJob { job_id }), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotJobOwner {}));
+        assert_eq!(query_balance(&app, &cw20_addr, &user2), Uint128::new(350));
+
+        // only the configured token may call the hook
+        let err = app
+            .execute_contract(
+                user1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::Receive(Cw20ReceiveMsg {
+                    sender: user1.to_string(),
+                    amount: Uint128::new(1000),
+                    msg: to_json_binary(&ReceiveMsg::FundJob { job_id }).unwrap(),
+                }),
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn test_native_asset() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        app.init_modules(|router, _, storage| {
+            for addr in [&user1, &user2, &agent1] {
+                router.bank.init_balance(storage, addr, coins(500, "inj")).unwrap();
+            }
+        });
+        let agent_work_addr = setup_agent_work_contract_with_config(
+            &mut app,
+            admin.clone(),
+            Asset::Native { denom: "inj".to_string() },
+            None,
+        );
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+
+        // the attached funds must match the staked amount
+        let user_stake_msg = ExecuteMsg::UserStake {
+            amount: Uint128::new(100),
+            job_id,
+        };
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &user_stake_msg, &coins(50, "inj"))
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidFunds { .. }));
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &user_stake_msg, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidFunds { .. }));
+
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &user_stake_msg, &coins(100, "inj"))
+            .unwrap();
+        hire_agent(&mut app, agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        app.execute_contract(
+            agent1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::AgentStake {
+                amount: Uint128::new(10),
+                job_id,
+                collateral_asset: None,
+            },
+            &coins(10, "inj"),
+        )
+        .unwrap();
+        app.execute_contract(
+            user2.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::RegisterJuror { amount: Uint128::new(100) },
+            &coins(100, "inj"),
+        )
+        .unwrap();
+        assert_eq!(app.wrap().query_balance(&agent_work_addr, "inj").unwrap().amount, Uint128::new(210));
+
+        // there is no cw20 token to send the hook
+        let err = app
+            .execute_contract(
+                user1.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::Receive(Cw20ReceiveMsg {
+                    sender: user1.to_string(),
+                    amount: Uint128::new(100),
+                    msg: to_json_binary(&ReceiveMsg::FundJob { job_id }).unwrap(),
+                }),
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+
+        // withdrawals are bank sends
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_id);
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::DistributeRewardsByAgent { job_id },
+            &[],
+        )
+        .unwrap();
+        withdraw_all(&mut app, &agent_work_addr, &agent1);
+        assert_eq!(app.wrap().query_balance(&agent1, "inj").unwrap().amount, Uint128::new(600));
+        assert_eq!(app.wrap().query_balance(&user1, "inj").unwrap().amount, Uint128::new(400));
+
+        app.execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::UnregisterJuror {}, &[])
+            .unwrap();
+        app.update_block(|block| {
+            block.time = block.time.plus_seconds(DisputeConfig::default().juror_unbonding_period)
+        });
+        app.execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::WithdrawJurorBond {}, &[])
+            .unwrap();
+        assert_eq!(app.wrap().query_balance(&user2, "inj").unwrap().amount, Uint128::new(500));
+
+        let assets: AssetsResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAssets {})
+            .unwrap();
+        assert_eq!(assets.bond_asset, Asset::Native { denom: "inj".to_string() });
+        assert_eq!(assets.accepted_assets.len(), 1);
+    }
+
+    #[test]
+    fn test_multi_asset_jobs() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+        app.init_modules(|router, _, storage| {
+            for addr in [&user1, &agent1] {
+                router.bank.init_balance(storage, addr, coins(500, "inj")).unwrap();
+            }
+        });
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+        let inj = Asset::Native { denom: "inj".to_string() };
+        let ttk = Asset::Cw20 { addr: cw20_addr.clone() };
+
+        // only the admin manages the whitelist
+        let add_inj = ExecuteMsg::AddAsset {
+            token_denom: "INJ".to_string(),
+            asset: inj.clone(),
+        };
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &add_inj, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        let create_inj_job = ExecuteMsg::CreateJob {
+            title: "Test Job".to_string(),
+            spec_uri: "ipfs://job-spec".to_string(),
+            spec_hash: None,
+            budget: Uint128::new(100),
+            payment_asset: inj.clone(),
+            min_agent_stake: Uint128::zero(),
+            max_agents: None,
+        };
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &create_inj_job, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AssetNotAccepted { .. }));
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &add_inj, &[])
+            .unwrap();
+
+        // the job is paid in inj and the agent puts up TTK as collateral
+        let job_id = create_job_in(&mut app, agent_work_addr.clone(), user1.clone(), inj.clone());
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::UserStake {
+                amount: Uint128::new(100),
+                job_id,
+            },
+            &coins(100, "inj"),
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                user1.clone(),
+                cw20_addr.clone(),
+                &Cw20ExecuteMsg::Send {
+                    contract: agent_work_addr.to_string(),
+                    amount: Uint128::new(100),
+                    msg: to_json_binary(&ReceiveMsg::FundJob { job_id }).unwrap(),
                 },
                 &[],
-                "Agent Work",
-                None,
             )
-            .unwrap();
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::WrongAsset { .. }));
 
-        agent_work_addr
-    }
-
-    fn allocate_token(
-        app: &mut App,
-        admin: Addr,
-        cw20_addr: Addr,
-        user1: Addr,
-        user2: Addr,
-        agent1: Addr,
-        agent2: Addr,
-        agent3: Addr,
-    ) {
-        // send 500 TTK to user1, user2, agent1, agent2, agent3
         app.execute_contract(
-            admin.clone(),
+            agent1.clone(),
             cw20_addr.clone(),
-            &Cw20ExecuteMsg::Transfer {
-                recipient: user1.to_string(),
-                amount: Uint128::new(500),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: agent_work_addr.to_string(),
+                amount: Uint128::new(20),
+                expires: None,
             },
             &[],
         )
         .unwrap();
+        hire_agent(&mut app, agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        let agent_stake_msg = |collateral_asset: Option<Asset>| ExecuteMsg::AgentStake {
+            amount: Uint128::new(10),
+            job_id,
+            collateral_asset,
+        };
+        app.execute_contract(agent1.clone(), agent_work_addr.clone(), &agent_stake_msg(Some(ttk.clone())), &[])
+            .unwrap();
+        // a top up in another asset than the first stake is refused
+        let err = app
+            .execute_contract(agent1.clone(), agent_work_addr.clone(), &agent_stake_msg(None), &coins(10, "inj"))
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::WrongAsset { .. }));
+
+        let user_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetUserStake { user_addr: user1.clone(), job_id })
+            .unwrap();
+        assert_eq!(user_stake, AssetAmount { asset: inj.clone(), amount: Uint128::new(100) });
+        let agent_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAgentStake { agent_addr: agent1.clone(), job_id })
+            .unwrap();
+        assert_eq!(agent_stake, AssetAmount { asset: ttk.clone(), amount: Uint128::new(10) });
+
+        // the reward is paid in inj and the collateral returned in TTK
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_id);
         app.execute_contract(
-            admin.clone(),
-            cw20_addr.clone(),
-            &Cw20ExecuteMsg::Transfer {
-                recipient: user2.to_string(),
-                amount: Uint128::new(500),
-            },
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::DistributeRewardsByAgent { job_id },
             &[],
         )
         .unwrap();
+        let claimable: Vec<AssetAmount> = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetClaimable { addr: agent1.clone() })
+            .unwrap();
+        assert_eq!(claimable.len(), 2);
+        withdraw_all(&mut app, &agent_work_addr, &agent1);
+        assert_eq!(app.wrap().query_balance(&agent1, "inj").unwrap().amount, Uint128::new(600));
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(500));
+
+        // removing an asset only stops new jobs from using it, and drops its arbiter limit
         app.execute_contract(
             admin.clone(),
-            cw20_addr.clone(),
-            &Cw20ExecuteMsg::Transfer {
-                recipient: agent1.to_string(),
-                amount: Uint128::new(500),
+            agent_work_addr.clone(),
+            &ExecuteMsg::UpdateConfig {
+                treasury: None,
+                protocol_fee_bps: None,
+                arbiter_max_escrow: Some(AssetAmount { asset: inj.clone(), amount: Uint128::new(100) }),
             },
             &[],
         )
         .unwrap();
         app.execute_contract(
             admin.clone(),
-            cw20_addr.clone(),
-            &Cw20ExecuteMsg::Transfer {
-                recipient: agent2.to_string(),
-                amount: Uint128::new(500),
-            },
+            agent_work_addr.clone(),
+            &ExecuteMsg::RemoveAsset { asset: inj.clone() },
             &[],
         )
         .unwrap();
-        app.execute_contract(
+        let limits: Vec<AssetAmount> = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetArbiterMaxEscrow {})
+            .unwrap();
+        assert!(limits.is_empty());
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &create_inj_job, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AssetNotAccepted { .. }));
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id })
+            .unwrap();
+        assert_eq!(job.payment_asset, inj);
+        let assets: AssetsResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAssets {})
+            .unwrap();
+        assert_eq!(assets.accepted_assets.len(), 1);
+
+        // the bonds are paid in the remaining asset, so it stays
+        let err = app
+            .execute_contract(
+                admin.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::RemoveAsset { asset: assets.bond_asset.clone() },
+                &[],
+            )
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::BondAssetNotRemovable { .. }));
+        assert_eq!(assets.bond_asset, ttk);
+    }
+
+    #[test]
+    fn test_protocol_fee() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+        let treasury = app.api().addr_make("treasury");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
             admin.clone(),
             cw20_addr.clone(),
-            &Cw20ExecuteMsg::Transfer {
-                recipient: agent3.to_string(),
-                amount: Uint128::new(500),
-            },
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+        let ttk = Asset::Cw20 { addr: cw20_addr.clone() };
+
+        // only the admin updates the config, with a fee of at most 100%
+        let update_config = |protocol_fee_bps: u16| ExecuteMsg::UpdateConfig {
+            treasury: Some(treasury.clone()),
+            protocol_fee_bps: Some(protocol_fee_bps),
+            arbiter_max_escrow: None,
+        };
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &update_config(1_000), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        let err = app
+            .execute_contract(admin.clone(), agent_work_addr.clone(), &update_config(10_001), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidConfig { .. }));
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &update_config(1_000), &[])
+            .unwrap();
+        let config: Config = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetConfig {})
+            .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                treasury: treasury.clone(),
+                protocol_fee_bps: 1_000,
+            }
+        );
+
+        // 10% of the escrow goes to the treasury, the agent stake is repaid in full
+        let job_1 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_1, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_1, 10);
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_1);
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::DistributeRewardsByAgent { job_id: job_1 },
             &[],
         )
         .unwrap();
+        let claimable: Vec<AssetAmount> = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetClaimable { addr: agent1.clone() })
+            .unwrap();
+        assert_eq!(claimable, vec![AssetAmount { asset: ttk.clone(), amount: Uint128::new(100) }]);
+
+        // the fee is taken from each payout per time unit as well
+        let job_2 = create_job(&mut app, agent_work_addr.clone(), user2.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user2.clone(), job_2, 50);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_2, 10);
+        complete_job(&mut app, agent_work_addr.clone(), user2.clone(), agent2.clone(), job_2);
+        app.execute_contract(
+            user2.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::DistributeRewardsByTime { job_id: job_2 },
+            &[],
+        )
+        .unwrap();
+        let claimable: Vec<AssetAmount> = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetClaimable { addr: agent2.clone() })
+            .unwrap();
+        assert_eq!(claimable, vec![AssetAmount { asset: ttk.clone(), amount: Uint128::new(9) }]);
+
+        let fees: Vec<AssetAmount> = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetProtocolFees {})
+            .unwrap();
+        assert_eq!(fees, vec![AssetAmount { asset: ttk.clone(), amount: Uint128::new(11) }]);
+        withdraw_all(&mut app, &agent_work_addr, &treasury);
+        assert_eq!(query_balance(&app, &cw20_addr, &treasury), Uint128::new(11));
     }
 
     #[test]
-    fn test_user_stake_and_unstake() {
+    fn test_pause() {
         let mut app = App::default();
         let admin = app.api().addr_make("admin");
         let user1 = app.api().addr_make("user1");
@@ -542,12 +6119,10 @@ mod tests {
         let agent1 = app.api().addr_make("agent1");
         let agent2 = app.api().addr_make("agent2");
         let agent3 = app.api().addr_make("agent3");
+        let guardian = app.api().addr_make("guardian");
 
-        // set up cw20 contract
         let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
-        // set up agent work contract
         let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
-        // allocate 500 TTK to user1 and user2
         allocate_token(
             &mut app,
             admin.clone(),
@@ -558,101 +6133,380 @@ mod tests {
             agent2.clone(),
             agent3.clone(),
         );
+        app.execute_contract(
+            admin.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::GrantRole { role: Role::Guardian, addr: guardian.clone() },
+            &[],
+        )
+        .unwrap();
 
-        // user1 give allowance and stake 200 TTK
+        // agent1 has a payout credited before the pause
+        let job_1 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_1, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_1, 10);
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_1);
         app.execute_contract(
             user1.clone(),
-            cw20_addr.clone(),
-            &Cw20ExecuteMsg::IncreaseAllowance {
-                spender: agent_work_addr.to_string(),
-                amount: Uint128::new(200),
-                expires: None,
-            },
+            agent_work_addr.clone(),
+            &ExecuteMsg::DistributeRewardsByAgent { job_id: job_1 },
             &[],
         )
         .unwrap();
-        let response = app
+
+        // user1 disputes the work of agent2 before the pause
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent3.clone(), 100);
+        let job_3 = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_3, 100);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_3, 10);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent2.clone(), job_3);
+        open_dispute(&mut app, agent_work_addr.clone(), user1.clone(), job_3);
+        let disputed: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id: job_3 })
+            .unwrap();
+
+        // only the admin or the guardian can pause
+        let freeze = PauseFlags { staking: true, payouts: true, voting: true, withdrawals: false };
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::Pause { flags: freeze.clone() }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        app.execute_contract(guardian.clone(), agent_work_addr.clone(), &ExecuteMsg::Pause { flags: freeze.clone() }, &[])
+            .unwrap();
+
+        // everything but withdrawals is frozen, jobs can still be created
+        let job_2 = create_job(&mut app, agent_work_addr.clone(), user2.clone());
+        let err = app
+            .execute_contract(
+                user2.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::UserStake { amount: Uint128::new(100), job_id: job_2 },
+                &[],
+            )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Paused { operation } => assert_eq!(operation, "staking"),
+            err => panic!("unexpected error {err}"),
+        }
+        let err = app
             .execute_contract(
                 user1.clone(),
                 agent_work_addr.clone(),
-                &ExecuteMsg::UserStake {
-                    amount: Uint128::new(200),
-                    job_id: Uint128::new(1),
-                },
+                &ExecuteMsg::CommitVote { job_id: job_1, hash: vote_commitment(job_1, 0, &user1, true, "salt") },
                 &[],
             )
-            .unwrap();
-        assert!(response.events.iter().any(|e| e.ty == "wasm"
-            && e.attributes
-                .iter()
-                .any(|attr| attr.key == "action" && attr.value == "user stake")));
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Paused { .. }));
+        withdraw_all(&mut app, &agent_work_addr, &agent1);
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(600));
 
-        // check whether current user1 balance is 300 and user1 stake is 200
-        let user1_balance: cw20::BalanceResponse = app
+        // flags are lifted one at a time
+        app.execute_contract(
+            admin.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::Unpause { flags: PauseFlags { staking: true, ..PauseFlags::default() } },
+            &[],
+        )
+        .unwrap();
+        let paused: PauseFlags = app
             .wrap()
-            .query_wasm_smart(
-                &cw20_addr,
-                &Cw20QueryMsg::Balance {
-                    address: user1.to_string(),
-                },
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetPauseFlags {})
+            .unwrap();
+        assert_eq!(paused, PauseFlags { staking: false, ..freeze });
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user2.clone(), job_2, 100);
+        let err = app
+            .execute_contract(
+                user2.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::UserUnstake { amount: Uint128::new(100), job_id: job_2 },
+                &[],
             )
+            .unwrap_err();
+        match err.downcast::<ContractError>().unwrap() {
+            ContractError::Paused { operation } => assert_eq!(operation, "payouts"),
+            err => panic!("unexpected error {err}"),
+        }
+
+        // lifting voting gives the open dispute back the time it was paused for
+        app.update_block(|block| block.time = block.time.plus_seconds(1_000));
+        app.execute_contract(
+            guardian.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::Unpause { flags: PauseFlags { voting: true, ..PauseFlags::default() } },
+            &[],
+        )
+        .unwrap();
+        let dispute: DisputeResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetDispute { job_id: job_3 })
             .unwrap();
-        assert_eq!(user1_balance.balance, Uint128::new(300));
-        let user1_stake: Uint128 = app
+        assert_eq!(dispute.commit_deadline, disputed.commit_deadline.plus_seconds(1_000));
+        assert_eq!(dispute.reveal_deadline, disputed.reveal_deadline.plus_seconds(1_000));
+        app.execute_contract(
+            agent3.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::CommitVote { job_id: job_3, hash: vote_commitment(job_3, 0, &agent3, false, "salt") },
+            &[],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_roles() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+        let arbiter = app.api().addr_make("arbiter");
+        let fee_manager = app.api().addr_make("fee_manager");
+        let juror_manager = app.api().addr_make("juror_manager");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
+            cw20_addr.clone(),
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
+            agent2.clone(),
+            agent3.clone(),
+        );
+
+        // only admins grant roles
+        let grant = |role: Role, addr: &Addr| ExecuteMsg::GrantRole { role, addr: addr.clone() };
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &grant(Role::Arbiter, &user1), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        for (role, addr) in [(Role::Arbiter, &arbiter), (Role::FeeManager, &fee_manager), (Role::JurorManager, &juror_manager)] {
+            app.execute_contract(admin.clone(), agent_work_addr.clone(), &grant(role, addr), &[])
+                .unwrap();
+        }
+        let has_role = |app: &App, role: Role, addr: &Addr| -> bool {
+            app.wrap()
+                .query_wasm_smart(&agent_work_addr, &QueryMsg::HasRole { role, addr: addr.clone() })
+                .unwrap()
+        };
+        assert!(has_role(&app, Role::FeeManager, &fee_manager));
+        assert!(!has_role(&app, Role::Arbiter, &fee_manager));
+        assert!(has_role(&app, Role::Arbiter, &admin));
+        let members: RoleMembersResponse = app
             .wrap()
-            .query_wasm_smart(
-                &agent_work_addr,
-                &QueryMsg::GetUserStake {
-                    user_addr: user1.clone(),
-                    job_id: Uint128::new(1),
-                },
-            )
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::ListRoleMembers { role: Role::Arbiter, start_after: None, limit: None })
             .unwrap();
-        assert_eq!(user1_stake, Uint128::new(200));
+        assert_eq!(members.members, vec![arbiter.clone()]);
 
-        // user1 unstake 100
-        let response = app
+        // each config field is guarded by its own role
+        let ttk = Asset::Cw20 { addr: cw20_addr.clone() };
+        let update_config = |protocol_fee_bps: Option<u16>, arbiter_max_escrow: Option<u128>| ExecuteMsg::UpdateConfig {
+            treasury: None,
+            protocol_fee_bps,
+            arbiter_max_escrow: arbiter_max_escrow.map(|amount| AssetAmount { asset: ttk.clone(), amount: Uint128::new(amount) }),
+        };
+        app.execute_contract(fee_manager.clone(), agent_work_addr.clone(), &update_config(Some(0), None), &[])
+            .unwrap();
+        let err = app
+            .execute_contract(fee_manager.clone(), agent_work_addr.clone(), &update_config(None, Some(100)), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &update_config(None, Some(50)), &[])
+            .unwrap();
+        let err = app
             .execute_contract(
-                user1.clone(),
-                agent_work_addr.clone(),
-                &ExecuteMsg::UserUnstake {
-                    amount: Uint128::new(100),
-                    job_id: Uint128::new(1),
+                admin.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::UpdateConfig {
+                    treasury: None,
+                    protocol_fee_bps: None,
+                    arbiter_max_escrow: Some(AssetAmount {
+                        asset: Asset::Native { denom: "inj".to_string() },
+                        amount: Uint128::new(100),
+                    }),
                 },
                 &[],
             )
-            .unwrap();
-        assert!(response.events.iter().any(|e| e.ty == "wasm"
-            && e.attributes
-                .iter()
-                .any(|attr| attr.key == "action" && attr.value == "user unstake")));
-
-        // check whether current user1 balance is 400 and user1 stake is 100
-        let user1_balance: cw20::BalanceResponse = app
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AssetNotAccepted { .. }));
+        let limits: Vec<AssetAmount> = app
             .wrap()
-            .query_wasm_smart(
-                &cw20_addr,
-                &Cw20QueryMsg::Balance {
-                    address: user1.to_string(),
-                },
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetArbiterMaxEscrow {})
+            .unwrap();
+        assert_eq!(limits, vec![AssetAmount { asset: ttk.clone(), amount: Uint128::new(50) }]);
+        let dispute_config = DisputeConfig { min_juror_bond: Uint128::new(50), ..DisputeConfig::default() };
+        let err = app
+            .execute_contract(
+                fee_manager.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::UpdateDisputeConfig { dispute_config: dispute_config.clone() },
+                &[],
             )
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        app.execute_contract(
+            juror_manager.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::UpdateDisputeConfig { dispute_config },
+            &[],
+        )
+        .unwrap();
+
+        // a disputed job escrowing 200 for a budget of 100, with 10 staked by agent1, is above
+        // the arbiter limit until the admin raises it
+        register_juror(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent3.clone(), 100);
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 200);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_id);
+        open_dispute(&mut app, agent_work_addr.clone(), user1.clone(), job_id);
+        let resolve = ExecuteMsg::ResolveDispute { job_id, is_accept: true };
+        let err = app
+            .execute_contract(arbiter.clone(), agent_work_addr.clone(), &resolve, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotArbitrable { .. }));
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &update_config(None, Some(100)), &[])
             .unwrap();
-        assert_eq!(user1_balance.balance, Uint128::new(400));
-        let user1_stake: Uint128 = app
+        let err = app
+            .execute_contract(arbiter.clone(), agent_work_addr.clone(), &resolve, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::NotArbitrable { max_escrow, .. } if max_escrow == Uint128::new(100)
+        ));
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &update_config(None, Some(200)), &[])
+            .unwrap();
+        let err = app
+            .execute_contract(arbiter.clone(), agent_work_addr.clone(), &resolve, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotArbitrable { .. }));
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &update_config(None, Some(210)), &[])
+            .unwrap();
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &resolve, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        app.execute_contract(arbiter.clone(), agent_work_addr.clone(), &resolve, &[])
+            .unwrap();
+        let job: JobResponse = app
             .wrap()
-            .query_wasm_smart(
-                &agent_work_addr,
-                &QueryMsg::GetUserStake {
-                    user_addr: user1.clone(),
-                    job_id: Uint128::new(1),
-                },
-            )
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id })
             .unwrap();
-        assert_eq!(user1_stake, Uint128::new(100));
+        assert_eq!(job.status, JobStatus::Completed);
+        withdraw_all(&mut app, &agent_work_addr, &agent1);
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(700));
+        let err = app
+            .execute_contract(arbiter.clone(), agent_work_addr.clone(), &resolve, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::DisputeFinalized {}));
+
+        // a small escrow does not let the arbiter rule on a large collateral
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 10);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_id, 400);
+        submit_job(&mut app, agent_work_addr.clone(), user1.clone(), agent2.clone(), job_id);
+        open_dispute(&mut app, agent_work_addr.clone(), user1.clone(), job_id);
+        let err = app
+            .execute_contract(arbiter.clone(), agent_work_addr.clone(), &ExecuteMsg::ResolveDispute { job_id, is_accept: false }, &[])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::NotArbitrable { max_escrow, .. } if max_escrow == Uint128::new(210)
+        ));
+
+        // a revoked role is gone
+        app.execute_contract(
+            admin.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::RevokeRole { role: Role::Arbiter, addr: arbiter.clone() },
+            &[],
+        )
+        .unwrap();
+        assert!(!has_role(&app, Role::Arbiter, &arbiter));
     }
 
     #[test]
-    fn test_agent_stake_and_unstake() {
+    fn test_admin_ownership() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        let query_ownership = |app: &App| -> Ownership {
+            app.wrap()
+                .query_wasm_smart(&agent_work_addr, &QueryMsg::GetOwnership {})
+                .unwrap()
+        };
+        assert_eq!(query_ownership(&app).owner, Some(admin.clone()));
+
+        // only the admin proposes, and not with an expiry already passed
+        let height = app.block_info().height;
+        let propose = |addr: &Addr, expiry: Option<Expiration>| ExecuteMsg::ProposeNewAdmin { addr: addr.clone(), expiry };
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &propose(&user1, None), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        let err = app
+            .execute_contract(admin.clone(), agent_work_addr.clone(), &propose(&user1, Some(Expiration::AtHeight(height))), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AdminTransferExpired {}));
+
+        // a proposal that is not accepted in time leaves the admin in place
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &propose(&user1, Some(Expiration::AtHeight(height + 10))), &[])
+            .unwrap();
+        let ownership = query_ownership(&app);
+        assert_eq!(ownership.owner, Some(admin.clone()));
+        assert_eq!(ownership.pending_owner, Some(user1.clone()));
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::AcceptAdmin {}, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+        app.update_block(|block| block.height += 10);
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::AcceptAdmin {}, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AdminTransferExpired {}));
+        assert_eq!(query_ownership(&app).owner, Some(admin.clone()));
+
+        // once accepted the previous admin loses every role
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &propose(&user1, None), &[])
+            .unwrap();
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::AcceptAdmin {}, &[])
+            .unwrap();
+        assert_eq!(
+            query_ownership(&app),
+            Ownership { owner: Some(user1.clone()), pending_owner: None, pending_expiry: None }
+        );
+        let remove_asset = ExecuteMsg::RemoveAsset { asset: Asset::Cw20 { addr: cw20_addr.clone() } };
+        let err = app
+            .execute_contract(admin.clone(), agent_work_addr.clone(), &remove_asset, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+
+        // renouncing drops the admin and any pending proposal
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &propose(&user2, None), &[])
+            .unwrap();
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::RenounceAdmin {}, &[])
+            .unwrap();
+        assert_eq!(query_ownership(&app), Ownership::default());
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &ExecuteMsg::AcceptAdmin {}, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NoPendingAdmin {}));
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &remove_asset, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {}));
+    }
+
+    #[test]
+    fn test_agent_slots() {
         let mut app = App::default();
         let admin = app.api().addr_make("admin");
         let user1 = app.api().addr_make("user1");
@@ -661,11 +6515,8 @@ mod tests {
         let agent2 = app.api().addr_make("agent2");
         let agent3 = app.api().addr_make("agent3");
 
-        // set up cw20 contract
         let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
-        // set up agent work contract
         let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
-        // allocate 500 TTK to user1, user2, agent1, agent2, agent3
         allocate_token(
             &mut app,
             admin.clone(),
@@ -676,98 +6527,117 @@ mod tests {
             agent2.clone(),
             agent3.clone(),
         );
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::CreateJob {
+                title: "Test Job".to_string(),
+                spec_uri: "ipfs://job-spec".to_string(),
+                spec_hash: None,
+                budget: Uint128::new(100),
+                payment_asset: Asset::Cw20 { addr: cw20_addr.clone() },
+                min_agent_stake: Uint128::new(50),
+                max_agents: None,
+            },
+            &[],
+        )
+        .unwrap();
+        let job_id = Uint128::one();
+        let num_of_agent = |app: &App| -> Uint128 {
+            app.wrap()
+                .query_wasm_smart(&agent_work_addr, &QueryMsg::GetNumOfAgent { job_id })
+                .unwrap()
+        };
+        let unstake = |app: &mut App, agent: &Addr, amount: u128| {
+            app.execute_contract(
+                agent.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::AgentUnstake { amount: Uint128::new(amount), job_id },
+                &[],
+            )
+        };
 
-        // agent1 give allowance and stake 200 TTK
+        // joining takes at least the minimum stake
         app.execute_contract(
             agent1.clone(),
             cw20_addr.clone(),
             &Cw20ExecuteMsg::IncreaseAllowance {
                 spender: agent_work_addr.to_string(),
-                amount: Uint128::new(200),
+                amount: Uint128::new(30),
                 expires: None,
             },
             &[],
         )
         .unwrap();
-        let response = app
+        hire_agent(&mut app, agent_work_addr.clone(), agent1.clone(), job_id, 10);
+        let err = app
             .execute_contract(
                 agent1.clone(),
                 agent_work_addr.clone(),
                 &ExecuteMsg::AgentStake {
-                    amount: Uint128::new(200),
-                    job_id: Uint128::new(1),
-                    cost_per_unit_time: Uint128::new(10),
+                    amount: Uint128::new(30),
+                    job_id,
+                    collateral_asset: None,
                 },
                 &[],
             )
-            .unwrap();
-        assert!(response.events.iter().any(|e| e.ty == "wasm"
-            && e.attributes
-                .iter()
-                .any(|attr| attr.key == "action" && attr.value == "agent stake")));
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::BelowMinAgentStake { min_agent_stake } if min_agent_stake == Uint128::new(50)
+        ));
 
-        // check whether current agent1 balance is 300 and agent1 stake is 200
-        let agent1_balance: cw20::BalanceResponse = app
-            .wrap()
-            .query_wasm_smart(
-                &cw20_addr,
-                &Cw20QueryMsg::Balance {
-                    address: agent1.to_string(),
-                },
-            )
-            .unwrap();
-        assert_eq!(agent1_balance.balance, Uint128::new(300));
-        let agent1_stake: Uint128 = app
-            .wrap()
-            .query_wasm_smart(
-                &agent_work_addr,
-                &QueryMsg::GetAgentStake {
-                    agent_addr: agent1.clone(),
-                    job_id: Uint128::new(1),
-                },
-            )
-            .unwrap();
-        assert_eq!(agent1_stake, Uint128::new(200));
+        // a top up keeps the single slot of the agent
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 50);
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent1.clone(), job_id, 50);
+        assert_eq!(num_of_agent(&app), Uint128::one());
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_id, 100);
+        assert_eq!(num_of_agent(&app), Uint128::new(2));
 
-        // agent1 unstake 100
-        let response = app
-            .execute_contract(
-                agent1.clone(),
-                agent_work_addr.clone(),
-                &ExecuteMsg::AgentUnstake {
-                    amount: Uint128::new(100),
-                    job_id: Uint128::new(1),
-                },
-                &[],
-            )
-            .unwrap();
+        // a partial unstake keeps the slot as long as the minimum stays staked
+        let err = unstake(&mut app, &agent1, 60).unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::BelowMinAgentStake { min_agent_stake } if min_agent_stake == Uint128::new(50)
+        ));
+        unstake(&mut app, &agent1, 50).unwrap();
+        assert_eq!(num_of_agent(&app), Uint128::new(2));
 
-        // check whether current agent1 balance is 400 and agent1 stake is 100
-        let agent1_balance: cw20::BalanceResponse = app
-            .wrap()
-            .query_wasm_smart(
-                &cw20_addr,
-                &Cw20QueryMsg::Balance {
-                    address: agent1.to_string(),
-                },
-            )
-            .unwrap();
-        assert_eq!(agent1_balance.balance, Uint128::new(400));
-        let agent1_stake: Uint128 = app
+        // unstaking everything releases the slot, staking again takes it back once
+        unstake(&mut app, &agent2, 100).unwrap();
+        assert_eq!(num_of_agent(&app), Uint128::one());
+        let err = app
+            .execute_contract(agent2.clone(), agent_work_addr.clone(), &ExecuteMsg::SubmitWork { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotJobAgent {}));
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_id, 60);
+        assert_eq!(num_of_agent(&app), Uint128::new(2));
+
+        // each agent is paid its stake back and one share of the rewards
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 100);
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), job_id);
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::DistributeRewardsByAgent { job_id },
+            &[],
+        )
+        .unwrap();
+        // the paid agents are off the job
+        assert_eq!(num_of_agent(&app), Uint128::zero());
+        withdraw_all(&mut app, &agent_work_addr, &agent1);
+        withdraw_all(&mut app, &agent_work_addr, &agent2);
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(550));
+        assert_eq!(query_balance(&app, &cw20_addr, &agent2), Uint128::new(550));
+        let balance: cw20::BalanceResponse = app
             .wrap()
-            .query_wasm_smart(
-                &agent_work_addr,
-                &QueryMsg::GetAgentStake {
-                    agent_addr: agent1.clone(),
-                    job_id: Uint128::new(1),
-                },
-            )
+            .query_wasm_smart(&cw20_addr, &Cw20QueryMsg::Balance { address: agent_work_addr.to_string() })
             .unwrap();
-        assert_eq!(agent1_stake, Uint128::new(100));
+        assert_eq!(balance.balance, Uint128::zero());
     }
 
     #[test]
-    fn test_distribute_rewards_by_agent() {
+    fn test_applications() {
         let mut app = App::default();
         let admin = app.api().addr_make("admin");
         let user1 = app.api().addr_make("user1");
@@ -776,11 +6646,8 @@ mod tests {
         let agent2 = app.api().addr_make("agent2");
         let agent3 = app.api().addr_make("agent3");
 
-        // set up cw20 contract
         let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
-        // set up agent work contract
         let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
-        // allocate 500 TTK to user1, user2, agent1, agent2, agent3
         allocate_token(
             &mut app,
             admin.clone(),
@@ -791,140 +6658,388 @@ mod tests {
             agent2.clone(),
             agent3.clone(),
         );
-
-        // user1 give allowance and stake 100 TTK
-        app.execute_contract(
-            user1.clone(),
-            cw20_addr.clone(),
-            &Cw20ExecuteMsg::IncreaseAllowance {
-                spender: agent_work_addr.to_string(),
-                amount: Uint128::new(100),
-                expires: None,
-            },
-            &[],
-        )
-        .unwrap();
         app.execute_contract(
             user1.clone(),
             agent_work_addr.clone(),
-            &ExecuteMsg::UserStake {
-                amount: Uint128::new(100),
-                job_id: Uint128::new(1),
+            &ExecuteMsg::CreateJob {
+                title: "Test Job".to_string(),
+                spec_uri: "ipfs://job-spec".to_string(),
+                spec_hash: None,
+                budget: Uint128::new(100),
+                payment_asset: Asset::Cw20 { addr: cw20_addr.clone() },
+                min_agent_stake: Uint128::zero(),
+                max_agents: Some(1),
             },
             &[],
         )
         .unwrap();
-
-        // agent1 give allowance and stake 10 TTK
+        let job_id = Uint128::one();
+        let apply = |agent: &Addr, rate: u128| (agent.clone(), ExecuteMsg::ApplyToJob {
+            job_id,
+            proposed_rate: Uint128::new(rate),
+            proposal_uri: format!("ipfs://{agent}"),
+        });
+        let accept = |agent: &Addr| ExecuteMsg::AcceptApplication { job_id, agent: agent.clone() };
+        let reject = |agent: &Addr| ExecuteMsg::RejectApplication { job_id, agent: agent.clone() };
+        let stake = ExecuteMsg::AgentStake {
+            amount: Uint128::new(10),
+            job_id,
+            collateral_asset: None,
+        };
         app.execute_contract(
             agent1.clone(),
             cw20_addr.clone(),
             &Cw20ExecuteMsg::IncreaseAllowance {
                 spender: agent_work_addr.to_string(),
-                amount: Uint128::new(10),
+                amount: Uint128::new(20),
                 expires: None,
             },
             &[],
         )
         .unwrap();
+
+        // staking takes an accepted application
+        let err = app
+            .execute_contract(agent1.clone(), agent_work_addr.clone(), &stake, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotHired {}));
+        for (agent, msg) in [apply(&agent1, 10), apply(&agent2, 20)] {
+            app.execute_contract(agent, agent_work_addr.clone(), &msg, &[]).unwrap();
+        }
+        let (agent, msg) = apply(&agent1, 15);
+        let err = app.execute_contract(agent, agent_work_addr.clone(), &msg, &[]).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AlreadyApplied {}));
+        let err = app
+            .execute_contract(agent1.clone(), agent_work_addr.clone(), &stake, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotHired {}));
+
+        // applications are listed by agent address
+        let list = |app: &App, start_after: Option<Addr>, limit: Option<u32>| -> Vec<ApplicationResponse> {
+            let response: ListApplicationsResponse = app
+                .wrap()
+                .query_wasm_smart(&agent_work_addr, &QueryMsg::ListApplications { job_id, start_after, limit })
+                .unwrap();
+            response.applications
+        };
+        let applications = list(&app, None, None);
+        assert_eq!(applications.len(), 2);
+        assert!(applications.iter().all(|application| application.status == ApplicationStatus::Pending));
+        let first = list(&app, None, Some(1));
+        assert_eq!(first.len(), 1);
+        let rest = list(&app, Some(first[0].agent.clone()), None);
+        assert_eq!(rest.len(), 1);
+        assert_ne!(rest[0].agent, first[0].agent);
+        let application = applications.iter().find(|application| application.agent == agent2).unwrap();
+        assert_eq!(application.proposed_rate, Uint128::new(20));
+        assert_eq!(application.proposal_uri, format!("ipfs://{agent2}"));
+
+        // only the owner decides, and hires no more agents than the job allows
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &accept(&agent1), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotJobOwner {}));
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &accept(&agent1), &[])
+            .unwrap();
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &accept(&agent2), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::MaxAgentsReached { max_agents: 1 }));
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &reject(&agent2), &[])
+            .unwrap();
+        let application: ApplicationResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetApplication { job_id, agent: agent2.clone() })
+            .unwrap();
+        assert_eq!(application.status, ApplicationStatus::Rejected);
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &reject(&agent2), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::ApplicationNotPending {}));
+
+        // a hired agent that has not staked yet can be let go, which frees its place
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &reject(&agent1), &[])
+            .unwrap();
+        let application: ApplicationResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetApplication { job_id, agent: agent1.clone() })
+            .unwrap();
+        assert_eq!(application.status, ApplicationStatus::Rejected);
+        let err = app
+            .execute_contract(agent1.clone(), agent_work_addr.clone(), &stake, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotHired {}));
+        let (agent, msg) = apply(&agent1, 10);
+        app.execute_contract(agent, agent_work_addr.clone(), &msg, &[]).unwrap();
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &accept(&agent1), &[])
+            .unwrap();
+
+        // a hired agent stakes at the rate it proposed
+        app.execute_contract(agent1.clone(), agent_work_addr.clone(), &stake, &[])
+            .unwrap();
+        let rates: Vec<AgentRateResponse> = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAgentRates { job_id })
+            .unwrap();
+        assert_eq!(rates, vec![AgentRateResponse { agent: agent1.clone(), cost_per_unit_time: Uint128::new(10) }]);
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &reject(&agent1), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AgentOnJob {}));
+
+        // withdrawing gives the place back once the agent left the job
+        let withdraw = ExecuteMsg::WithdrawApplication { job_id };
+        let err = app
+            .execute_contract(agent3.clone(), agent_work_addr.clone(), &withdraw, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::ApplicationNotFound {}));
+        let err = app
+            .execute_contract(agent1.clone(), agent_work_addr.clone(), &withdraw, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AgentOnJob {}));
         app.execute_contract(
             agent1.clone(),
             agent_work_addr.clone(),
-            &ExecuteMsg::AgentStake {
-                amount: Uint128::new(10),
-                job_id: Uint128::new(1),
-                cost_per_unit_time: Uint128::new(10),
-            },
+            &ExecuteMsg::AgentUnstake { amount: Uint128::new(10), job_id },
             &[],
         )
         .unwrap();
+        app.execute_contract(agent1.clone(), agent_work_addr.clone(), &withdraw, &[])
+            .unwrap();
+        assert_eq!(list(&app, None, None).len(), 1);
 
-        // agent2 give allowance and stake 10 TTK
-        app.execute_contract(
-            agent2.clone(),
+        // a rejected agent applies again at a lower rate and takes the free place
+        let (agent, msg) = apply(&agent2, 10);
+        app.execute_contract(agent, agent_work_addr.clone(), &msg, &[]).unwrap();
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &accept(&agent2), &[])
+            .unwrap();
+        agent_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), agent2.clone(), job_id, 10);
+        let err = app
+            .execute_contract(agent1.clone(), agent_work_addr.clone(), &stake, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotHired {}));
+    }
+
+    #[test]
+    fn test_auction() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+        let agent3 = app.api().addr_make("agent3");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
+        allocate_token(
+            &mut app,
+            admin.clone(),
             cw20_addr.clone(),
-            &Cw20ExecuteMsg::IncreaseAllowance {
-                spender: agent_work_addr.to_string(),
-                amount: Uint128::new(10),
-                expires: None,
-            },
-            &[],
-        )
-        .unwrap();
-        app.execute_contract(
+            user1.clone(),
+            user2.clone(),
+            agent1.clone(),
             agent2.clone(),
-            agent_work_addr.clone(),
-            &ExecuteMsg::AgentStake {
-                amount: Uint128::new(10),
-                job_id: Uint128::new(1),
-                cost_per_unit_time: Uint128::new(10),
-            },
-            &[],
-        )
-        .unwrap();
-
-        // agent3 give allowance and stake 10 TTK
-        app.execute_contract(
             agent3.clone(),
-            cw20_addr.clone(),
-            &Cw20ExecuteMsg::IncreaseAllowance {
-                spender: agent_work_addr.to_string(),
-                amount: Uint128::new(10),
-                expires: None,
-            },
-            &[],
-        )
-        .unwrap();
+        );
         app.execute_contract(
-            agent3.clone(),
-            agent_work_addr.clone(),
-            &ExecuteMsg::AgentStake {
-                amount: Uint128::new(10),
-                job_id: Uint128::new(1),
-                cost_per_unit_time: Uint128::new(10),
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::CreateJob {
+                title: "Test Job".to_string(),
+                spec_uri: "ipfs://job-spec".to_string(),
+                spec_hash: None,
+                budget: Uint128::new(100),
+                payment_asset: Asset::Cw20 { addr: cw20_addr.clone() },
+                min_agent_stake: Uint128::new(10),
+                max_agents: Some(2),
             },
             &[],
         )
         .unwrap();
-
-        // distribute rewards
-        let response = app
-            .execute_contract(
-                user1.clone(),
+        let job_id = Uint128::one();
+        let open_auction = |slots: u32| ExecuteMsg::OpenAuction { job_id, slots, commit_period: 100, reveal_period: 100 };
+        // bids are salted with the bidder address
+        let commit_bid = |app: &mut App, bidder: &Addr, rate: u128, collateral: u128, deposit: u128| {
+            app.execute_contract(
+                bidder.clone(),
+                cw20_addr.clone(),
+                &Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: agent_work_addr.to_string(),
+                    amount: Uint128::new(deposit),
+                    expires: None,
+                },
+                &[],
+            )
+            .unwrap();
+            app.execute_contract(
+                bidder.clone(),
                 agent_work_addr.clone(),
-                &ExecuteMsg::DistributeRewardsByAgent {
-                    job_id: Uint128::new(1)
+                &ExecuteMsg::CommitBid {
+                    job_id,
+                    hash: bid_commitment(job_id, bidder, Uint128::new(rate), Uint128::new(collateral), bidder.as_str()),
+                    deposit: Uint128::new(deposit),
+                },
+                &[],
+            )
+        };
+        let reveal_bid = |app: &mut App, bidder: &Addr, rate: u128, collateral: u128| {
+            app.execute_contract(
+                bidder.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::RevealBid {
+                    job_id,
+                    rate: Uint128::new(rate),
+                    collateral: Uint128::new(collateral),
+                    salt: bidder.to_string(),
                 },
                 &[],
             )
+        };
+        let settle = ExecuteMsg::SettleAuction { job_id };
+
+        // only the owner opens an auction, for no more agents than the job hires
+        let err = app
+            .execute_contract(user2.clone(), agent_work_addr.clone(), &open_auction(2), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotJobOwner {}));
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &open_auction(3), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::MaxAgentsReached { max_agents: 2 }));
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &open_auction(0), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidAuction { .. }));
+        let err = commit_bid(&mut app, &agent1, 8, 30, 40).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AuctionNotFound { .. }));
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &open_auction(2), &[])
             .unwrap();
 
-        // check whether current agent3 balance is 533 and agent1 stake is 0
-        let agent3_balance: cw20::BalanceResponse = app
+        // while it runs, agents are hired through bids only
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &open_auction(1), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AuctionInProgress {}));
+        let apply = ExecuteMsg::ApplyToJob { job_id, proposed_rate: Uint128::new(1), proposal_uri: String::new() };
+        let err = app
+            .execute_contract(agent1.clone(), agent_work_addr.clone(), &apply, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AuctionInProgress {}));
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::StartJob { job_id }, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AuctionInProgress {}));
+
+        // deposits can be larger than the collateral they hide
+        commit_bid(&mut app, &agent1, 8, 30, 40).unwrap();
+        commit_bid(&mut app, &agent2, 5, 20, 20).unwrap();
+        commit_bid(&mut app, &user2, 9, 30, 30).unwrap();
+        let err = commit_bid(&mut app, &agent1, 4, 30, 30).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AlreadyBid {}));
+        // the cw20 hook bids with the sent amount, here for more collateral than deposited
+        let hash = bid_commitment(job_id, &agent3, Uint128::new(3), Uint128::new(60), agent3.as_str());
+        app.execute_contract(
+            agent3.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: agent_work_addr.to_string(),
+                amount: Uint128::new(50),
+                msg: to_json_binary(&ReceiveMsg::CommitBid { job_id, hash }).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+        let bids: ListBidsResponse = app
             .wrap()
-            .query_wasm_smart(
-                &cw20_addr,
-                &Cw20QueryMsg::Balance {
-                    address: agent3.to_string(),
-                },
-            )
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::ListBids { job_id, start_after: None, limit: None })
             .unwrap();
-        assert_eq!(agent3_balance.balance, Uint128::new(533));
-        let agent3_stake: Uint128 = app
+        assert_eq!(bids.bids.len(), 4);
+        assert!(bids.bids.iter().all(|bid| bid.rate.is_none() && bid.collateral.is_none()));
+
+        // bids are revealed once the commit period is over
+        let err = reveal_bid(&mut app, &agent1, 8, 30).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotBidRevealPeriod {}));
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        let err = commit_bid(&mut app, &admin, 1, 10, 10).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::BiddingClosed {}));
+        let err = reveal_bid(&mut app, &agent1, 7, 30).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidBidReveal {}));
+        let err = reveal_bid(&mut app, &agent3, 3, 60).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidBidCollateral { .. }));
+        let err = reveal_bid(&mut app, &admin, 1, 10).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NoBidFound {}));
+        reveal_bid(&mut app, &agent1, 8, 30).unwrap();
+        reveal_bid(&mut app, &agent2, 5, 20).unwrap();
+        reveal_bid(&mut app, &user2, 9, 30).unwrap();
+        let err = app
+            .execute_contract(admin.clone(), agent_work_addr.clone(), &settle, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AuctionNotFinished {}));
+
+        // anyone settles, the two lowest revealed rates are hired and staked at their collateral
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        let err = reveal_bid(&mut app, &user2, 9, 30).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::NotBidRevealPeriod {}));
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &settle, &[])
+            .unwrap();
+        let err = app
+            .execute_contract(admin.clone(), agent_work_addr.clone(), &settle, &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::AuctionSettled {}));
+        let auction: Auction = app
             .wrap()
-            .query_wasm_smart(
-                &agent_work_addr,
-                &QueryMsg::GetAgentStake {
-                    agent_addr: agent3.clone(),
-                    job_id: Uint128::new(1),
-                },
-            )
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAuction { job_id })
+            .unwrap();
+        assert!(auction.settled);
+        let rates: Vec<AgentRateResponse> = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAgentRates { job_id })
             .unwrap();
-        assert_eq!(agent3_stake, Uint128::zero());
+        assert_eq!(rates.len(), 2);
+        assert!(rates.contains(&AgentRateResponse { agent: agent1.clone(), cost_per_unit_time: Uint128::new(8) }));
+        assert!(rates.contains(&AgentRateResponse { agent: agent2.clone(), cost_per_unit_time: Uint128::new(5) }));
+        for (agent, collateral) in [(&agent1, 30), (&agent2, 20), (&user2, 0)] {
+            let stake: AssetAmount = app
+                .wrap()
+                .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAgentStake { agent_addr: agent.clone(), job_id })
+                .unwrap();
+            assert_eq!(stake.amount, Uint128::new(collateral));
+        }
+        let application: ApplicationResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetApplication { job_id, agent: agent2.clone() })
+            .unwrap();
+        assert_eq!(application.status, ApplicationStatus::Accepted);
+
+        // losers and the excess deposit of winners are credited back, the deposit agent3 never
+        // revealed goes to the job owner
+        for (addr, balance) in [(&agent1, 470), (&agent2, 480), (&agent3, 450), (&user2, 500), (&user1, 550)] {
+            withdraw_all(&mut app, &agent_work_addr, addr);
+            assert_eq!(query_balance(&app, &cw20_addr, addr), Uint128::new(balance));
+        }
+
+        // the winners work the job and are paid their bid rate
+        user_stake(&mut app, cw20_addr.clone(), agent_work_addr.clone(), user1.clone(), job_id, 100);
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent2.clone(), job_id);
+        app.execute_contract(
+            user1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::DistributeRewardsByTime { job_id },
+            &[],
+        )
+        .unwrap();
+        for (agent, rate) in [(&agent1, 8), (&agent2, 5)] {
+            let claimable: Vec<AssetAmount> = app
+                .wrap()
+                .query_wasm_smart(&agent_work_addr, &QueryMsg::GetClaimable { addr: agent.clone() })
+                .unwrap();
+            assert_eq!(claimable[0].amount, Uint128::new(rate));
+        }
     }
 
     #[test]
-    fn test_distribute_rewards_by_time() {
+    fn test_auction_without_winners() {
         let mut app = App::default();
         let admin = app.api().addr_make("admin");
         let user1 = app.api().addr_make("user1");
@@ -933,11 +7048,8 @@ mod tests {
         let agent2 = app.api().addr_make("agent2");
         let agent3 = app.api().addr_make("agent3");
 
-        // set up cw20 contract
         let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
-        // set up agent work contract
         let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
-        // allocate 500 TTK to user1, user2, agent1, agent2, agent3
         allocate_token(
             &mut app,
             admin.clone(),
@@ -948,37 +7060,20 @@ mod tests {
             agent2.clone(),
             agent3.clone(),
         );
-
-        // user1 give allowance and stake 100 TTK
-        app.execute_contract(
-            user1.clone(),
-            cw20_addr.clone(),
-            &Cw20ExecuteMsg::IncreaseAllowance {
-                spender: agent_work_addr.to_string(),
-                amount: Uint128::new(100),
-                expires: None,
-            },
-            &[],
-        )
-        .unwrap();
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user1.clone());
         app.execute_contract(
             user1.clone(),
             agent_work_addr.clone(),
-            &ExecuteMsg::UserStake {
-                amount: Uint128::new(100),
-                job_id: Uint128::new(1),
-            },
+            &ExecuteMsg::OpenAuction { job_id, slots: 1, commit_period: 100, reveal_period: 100 },
             &[],
         )
         .unwrap();
-
-        // agent1 give allowance and stake 10 TTK
         app.execute_contract(
             agent1.clone(),
             cw20_addr.clone(),
             &Cw20ExecuteMsg::IncreaseAllowance {
                 spender: agent_work_addr.to_string(),
-                amount: Uint128::new(10),
+                amount: Uint128::new(20),
                 expires: None,
             },
             &[],
@@ -987,156 +7082,400 @@ mod tests {
         app.execute_contract(
             agent1.clone(),
             agent_work_addr.clone(),
-            &ExecuteMsg::AgentStake {
-                amount: Uint128::new(10),
-                job_id: Uint128::new(1),
-                cost_per_unit_time: Uint128::new(5),
+            &ExecuteMsg::CommitBid {
+                job_id,
+                hash: bid_commitment(job_id, &agent1, Uint128::new(10), Uint128::new(20), "salt"),
+                deposit: Uint128::new(20),
             },
             &[],
         )
         .unwrap();
-
-        // agent2 give allowance and stake 10 TTK
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
         app.execute_contract(
-            agent2.clone(),
-            cw20_addr.clone(),
-            &Cw20ExecuteMsg::IncreaseAllowance {
-                spender: agent_work_addr.to_string(),
-                amount: Uint128::new(10),
-                expires: None,
+            agent1.clone(),
+            agent_work_addr.clone(),
+            &ExecuteMsg::RevealBid {
+                job_id,
+                rate: Uint128::new(10),
+                collateral: Uint128::new(20),
+                salt: "salt".to_string(),
             },
             &[],
         )
         .unwrap();
+
+        // a job cancelled before the settlement hires nobody and credits every revealed deposit back
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &ExecuteMsg::CancelJob { job_id }, &[])
+            .unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::SettleAuction { job_id }, &[])
+            .unwrap();
+        let num_of_agent: Uint128 = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetNumOfAgent { job_id })
+            .unwrap();
+        assert_eq!(num_of_agent, Uint128::zero());
+        withdraw_all(&mut app, &agent_work_addr, &agent1);
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(500));
+        let bids: ListBidsResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::ListBids { job_id, start_after: None, limit: None })
+            .unwrap();
+        assert!(bids.bids.is_empty());
+    }
+
+    #[test]
+    fn test_auction_bid_limits() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let bidders: Vec<Addr> = (0..21).map(|bidder| app.api().addr_make(&format!("bidder{bidder}"))).collect();
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
         app.execute_contract(
-            agent2.clone(),
+            user1.clone(),
             agent_work_addr.clone(),
-            &ExecuteMsg::AgentStake {
-                amount: Uint128::new(10),
-                job_id: Uint128::new(1),
-                cost_per_unit_time: Uint128::new(10),
+            &ExecuteMsg::CreateJob {
+                title: "Test Job".to_string(),
+                spec_uri: "ipfs://job-spec".to_string(),
+                spec_hash: None,
+                budget: Uint128::new(100),
+                payment_asset: Asset::Cw20 { addr: cw20_addr.clone() },
+                min_agent_stake: Uint128::new(10),
+                max_agents: None,
             },
             &[],
         )
         .unwrap();
-
-        let response = app
-            .execute_contract(
-                user1.clone(),
-                agent_work_addr.clone(),
-                &ExecuteMsg::DistributeRewardsByTime {
-                    job_id: Uint128::new(1)
-                },
+        let job_id = Uint128::one();
+        let open_auction = |slots: u32| ExecuteMsg::OpenAuction { job_id, slots, commit_period: 100, reveal_period: 100 };
+        // every bidder bids its index as rate through the cw20 hook
+        let commit_bid = |app: &mut App, bidder: usize, deposit: u128| {
+            app.execute_contract(
+                admin.clone(),
+                cw20_addr.clone(),
+                &Cw20ExecuteMsg::Transfer { recipient: bidders[bidder].to_string(), amount: Uint128::new(deposit) },
                 &[],
             )
             .unwrap();
-
-        // check whether current user1 balance is 400 and user1 stake is 85
-        let user1_balance: cw20::BalanceResponse = app
-            .wrap()
-            .query_wasm_smart(
-                &cw20_addr,
-                &Cw20QueryMsg::Balance {
-                    address: user1.to_string(),
+            let hash = bid_commitment(job_id, &bidders[bidder], Uint128::new(bidder as u128), Uint128::new(10), "salt");
+            app.execute_contract(
+                bidders[bidder].clone(),
+                cw20_addr.clone(),
+                &Cw20ExecuteMsg::Send {
+                    contract: agent_work_addr.to_string(),
+                    amount: Uint128::new(deposit),
+                    msg: to_json_binary(&ReceiveMsg::CommitBid { job_id, hash }).unwrap(),
                 },
+                &[],
             )
+        };
+
+        // one settlement sorts at most 20 slots of 10 bids
+        let err = app
+            .execute_contract(user1.clone(), agent_work_addr.clone(), &open_auction(21), &[])
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidAuction { .. }));
+        app.execute_contract(user1.clone(), agent_work_addr.clone(), &open_auction(2), &[])
             .unwrap();
-        assert_eq!(user1_balance.balance, Uint128::new(400));
-        let user1_stake: Uint128 = app
+
+        // a deposit must cover the minimum stake, so filling the auction costs what real bids do
+        let err = commit_bid(&mut app, 0, 9).unwrap_err();
+        assert!(matches!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidBidCollateral { min_agent_stake, .. } if min_agent_stake == Uint128::new(10)
+        ));
+        for bidder in 0..20 {
+            commit_bid(&mut app, bidder, 10).unwrap();
+        }
+        let err = commit_bid(&mut app, 20, 10).unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::TooManyBids { max_bids: 20 }));
+        let auction: Auction = app
             .wrap()
-            .query_wasm_smart(
-                &agent_work_addr,
-                &QueryMsg::GetUserStake {
-                    user_addr: user1.clone(),
-                    job_id: Uint128::new(1),
-                },
-            )
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAuction { job_id })
             .unwrap();
-        assert_eq!(user1_stake, Uint128::new(85));
+        assert_eq!(auction.bids, 20);
 
-        // check whether current agent1 balance is 495 and agent1 stake is 10
-        let agent1_balance: cw20::BalanceResponse = app
-            .wrap()
-            .query_wasm_smart(
-                &cw20_addr,
-                &Cw20QueryMsg::Balance {
-                    address: agent1.to_string(),
+        // a full auction still settles, the two lowest rates are hired
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        for (bidder, addr) in bidders.iter().take(20).enumerate() {
+            app.execute_contract(
+                addr.clone(),
+                agent_work_addr.clone(),
+                &ExecuteMsg::RevealBid {
+                    job_id,
+                    rate: Uint128::new(bidder as u128),
+                    collateral: Uint128::new(10),
+                    salt: "salt".to_string(),
                 },
+                &[],
             )
             .unwrap();
-        assert_eq!(agent1_balance.balance, Uint128::new(495));
-        let agent1_stake: Uint128 = app
+        }
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        app.execute_contract(admin.clone(), agent_work_addr.clone(), &ExecuteMsg::SettleAuction { job_id }, &[])
+            .unwrap();
+        let rates: Vec<AgentRateResponse> = app
             .wrap()
-            .query_wasm_smart(
-                &agent_work_addr,
-                &QueryMsg::GetAgentStake {
-                    agent_addr: agent1.clone(),
-                    job_id: Uint128::new(1),
-                },
-            )
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAgentRates { job_id })
             .unwrap();
-        assert_eq!(agent1_stake, Uint128::new(10));
+        let mut hired: Vec<Addr> = rates.into_iter().map(|rate| rate.agent).collect();
+        hired.sort();
+        let mut expected = vec![bidders[0].clone(), bidders[1].clone()];
+        expected.sort();
+        assert_eq!(hired, expected);
+        assert_eq!(query_claimable(&app, &agent_work_addr, bidders[19].clone()), Uint128::new(10));
+    }
+
+    #[cw_serde]
+    struct BaselineTokenInfo {
+        token_denom: String,
+        token_address: Addr,
+    }
+
+    #[cw_serde]
+    struct BaselineInstantiateMsg {
+        cw20_addr: Addr,
+        user1: Addr,
+        agent1: Addr,
+        agent2: Addr,
+        juror: Addr,
+    }
+
+    // writes the storage of the first, unversioned deployment: user1 funded job 1 where
+    // agent1 staked, agent2 staked on job 7 nobody funded and one juror voted
+    fn baseline_instantiate(deps: DepsMut, _env: Env, _info: MessageInfo, msg: BaselineInstantiateMsg) -> StdResult<Response> {
+        let user_stake: Map<(Addr, String), Uint128> = Map::new("user_stake");
+        let agent_stake: Map<(Addr, String), Uint128> = Map::new("agent_stake");
+        let job_owner: Map<String, Addr> = Map::new("job_owner");
+        let job_agent: Map<String, Vec<Addr>> = Map::new("job_agent");
+        let BaselineInstantiateMsg { cw20_addr, user1, agent1, agent2, juror } = msg;
+
+        cw_storage_plus::Item::new("token_info").save(
+            deps.storage,
+            &BaselineTokenInfo { token_denom: "TTK".to_string(), token_address: cw20_addr },
+        )?;
+        user_stake.save(deps.storage, (user1.clone(), "1".to_string()), &Uint128::new(100))?;
+        job_owner.save(deps.storage, "1".to_string(), &user1)?;
+        agent_stake.save(deps.storage, (agent1.clone(), "1".to_string()), &Uint128::new(10))?;
+        job_agent.save(deps.storage, "1".to_string(), &vec![agent1.clone()])?;
+        agent_stake.save(deps.storage, (agent2.clone(), "7".to_string()), &Uint128::new(20))?;
+        job_agent.save(deps.storage, "7".to_string(), &vec![agent2])?;
+        let agent_cost: Map<Addr, Uint128> = Map::new("agent_cost");
+        agent_cost.save(deps.storage, agent1, &Uint128::new(10))?;
+        cw_storage_plus::Item::new("accpect_vote").save(deps.storage, &Uint128::one())?;
+        let is_juror_voted: Map<Addr, bool> = Map::new("is_juror_voted");
+        is_juror_voted.save(deps.storage, juror, &true)?;
+        Ok(Response::new())
     }
 
     #[test]
-    fn test_juror_vote() {
+    fn test_migrate() {
         let mut app = App::default();
         let admin = app.api().addr_make("admin");
         let user1 = app.api().addr_make("user1");
         let user2 = app.api().addr_make("user2");
         let agent1 = app.api().addr_make("agent1");
         let agent2 = app.api().addr_make("agent2");
-        let agent3 = app.api().addr_make("agent3");
 
-        // set up cw20 contract
         let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
-        // set up agent work contract
-        let agent_work_addr = setup_agent_work_contract(&mut app, admin.clone(), cw20_addr.clone());
-        // allocate 500 TTK to user1, user2, agent1, agent2, agent3
-        allocate_token(
-            &mut app,
+        let baseline_code_id = app.store_code(Box::new(ContractWrapper::new(execute, baseline_instantiate, query)));
+        let baseline_msg = BaselineInstantiateMsg {
+            cw20_addr: cw20_addr.clone(),
+            user1: user1.clone(),
+            agent1: agent1.clone(),
+            agent2: agent2.clone(),
+            juror: user2.clone(),
+        };
+        let agent_work_addr = app
+            .instantiate_contract(baseline_code_id, admin.clone(), &baseline_msg, &[], "Agent Work", Some(admin.to_string()))
+            .unwrap();
+        app.execute_contract(
             admin.clone(),
             cw20_addr.clone(),
-            user1.clone(),
-            user2.clone(),
-            agent1.clone(),
-            agent2.clone(),
-            agent3.clone(),
-        );
-
-        // Agent 1 Vote accept
-        app.execute_contract(
-            agent1.clone(),
-            agent_work_addr.clone(),
-            &ExecuteMsg::JurorVote { is_accept: true },
+            &Cw20ExecuteMsg::Transfer {
+                recipient: agent_work_addr.to_string(),
+                amount: Uint128::new(130),
+            },
             &[],
         )
         .unwrap();
+        let code_id = app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query).with_migrate(migrate)));
+
+        // the baseline stored no admin, so it has to be passed in
+        let err = app
+            .migrate_contract(admin.clone(), agent_work_addr.clone(), &MigrateMsg { baseline: None }, code_id)
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::BaselineSettingsRequired {}));
+        let migrate_msg = MigrateMsg {
+            baseline: Some(crate::msg::BaselineSettings {
+                admin: admin.clone(),
+                treasury: None,
+                protocol_fee_bps: 0,
+                dispute_config: None,
+            }),
+        };
+        app.migrate_contract(admin.clone(), agent_work_addr.clone(), &migrate_msg, code_id)
+            .unwrap();
+        let version = cw2::query_contract_info(&app.wrap(), agent_work_addr.to_string()).unwrap();
+        assert_eq!(version.contract, CONTRACT_NAME);
+        assert_eq!(version.version, CONTRACT_VERSION);
 
-        // Agent 2 vote accept
+        // the funded job is open with the owner stake as budget
+        let ttk = Asset::Cw20 { addr: cw20_addr.clone() };
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id: Uint128::one() })
+            .unwrap();
+        assert_eq!(job.owner, user1);
+        assert_eq!(job.status, JobStatus::Open);
+        assert_eq!(job.budget, Uint128::new(100));
+        assert_eq!(job.payment_asset, ttk);
+        let agent_stake: AssetAmount = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAgentStake { agent_addr: agent1.clone(), job_id: Uint128::one() })
+            .unwrap();
+        assert_eq!(agent_stake, AssetAmount { asset: ttk.clone(), amount: Uint128::new(10) });
+        let rates: Vec<AgentRateResponse> = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAgentRates { job_id: Uint128::one() })
+            .unwrap();
+        assert_eq!(rates, vec![AgentRateResponse { agent: agent1.clone(), cost_per_unit_time: Uint128::new(10) }]);
+        let application: ApplicationResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetApplication { job_id: Uint128::one(), agent: agent1.clone() })
+            .unwrap();
+        assert_eq!(application.status, ApplicationStatus::Accepted);
+        let assets: AssetsResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetAssets {})
+            .unwrap();
+        assert_eq!(assets.bond_asset, ttk);
+        let ownership: Ownership = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetOwnership {})
+            .unwrap();
+        assert_eq!(ownership.owner, Some(admin.clone()));
+
+        // the stake on the unfunded job is credited back, the shared tally is gone
+        withdraw_all(&mut app, &agent_work_addr, &agent2);
+        assert_eq!(query_balance(&app, &cw20_addr, &agent2), Uint128::new(20));
+        let storage = app.contract_storage(&agent_work_addr);
+        assert_eq!(storage.get(b"accpect_vote"), None);
+        assert_eq!(storage.get(b"token_info"), None);
+        drop(storage);
+        let job_id = create_job(&mut app, agent_work_addr.clone(), user2.clone());
+        assert_eq!(job_id, Uint128::new(2));
+
+        // the migrated job runs through the current lifecycle
+        complete_job(&mut app, agent_work_addr.clone(), user1.clone(), agent1.clone(), Uint128::one());
         app.execute_contract(
-            agent2.clone(),
+            user1.clone(),
             agent_work_addr.clone(),
-            &ExecuteMsg::JurorVote { is_accept: true },
+            &ExecuteMsg::DistributeRewardsByAgent { job_id: Uint128::one() },
             &[],
         )
         .unwrap();
+        withdraw_all(&mut app, &agent_work_addr, &agent1);
+        assert_eq!(query_balance(&app, &cw20_addr, &agent1), Uint128::new(110));
 
-        // Agent 3 vote reject
+        // migrating to the same version is allowed, downgrades and other contracts are not
+        app.migrate_contract(admin.clone(), agent_work_addr.clone(), &MigrateMsg { baseline: None }, code_id)
+            .unwrap();
+        cw2::set_contract_version(app.contract_storage_mut(&agent_work_addr).as_mut(), CONTRACT_NAME, "99.0.0")
+            .unwrap();
+        let err = app
+            .migrate_contract(admin.clone(), agent_work_addr.clone(), &MigrateMsg { baseline: None }, code_id)
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::CannotDowngrade { .. }));
+        cw2::set_contract_version(app.contract_storage_mut(&agent_work_addr).as_mut(), "crates.io:other", "0.1.0")
+            .unwrap();
+        let err = app
+            .migrate_contract(admin.clone(), agent_work_addr.clone(), &MigrateMsg { baseline: None }, code_id)
+            .unwrap_err();
+        assert!(matches!(err.downcast::<ContractError>().unwrap(), ContractError::CannotMigrate { .. }));
+    }
+
+    #[test]
+    fn test_migrate_out_of_range_job_ids() {
+        let mut app = App::default();
+        let admin = app.api().addr_make("admin");
+        let user1 = app.api().addr_make("user1");
+        let user2 = app.api().addr_make("user2");
+        let agent1 = app.api().addr_make("agent1");
+        let agent2 = app.api().addr_make("agent2");
+
+        let cw20_addr = setup_cw20_contract(&mut app, admin.clone());
+        let baseline_code_id = app.store_code(Box::new(ContractWrapper::new(execute, baseline_instantiate, query)));
+        let baseline_msg = BaselineInstantiateMsg {
+            cw20_addr: cw20_addr.clone(),
+            user1: user1.clone(),
+            agent1: agent1.clone(),
+            agent2: agent2.clone(),
+            juror: user2.clone(),
+        };
+        let agent_work_addr = app
+            .instantiate_contract(baseline_code_id, admin.clone(), &baseline_msg, &[], "Agent Work", Some(admin.to_string()))
+            .unwrap();
+        // job ids were picked by the stakers: user2 funded the largest job key and one past it
+        let last_key = Uint128::from(u64::MAX);
+        let past_last_key = last_key + Uint128::one();
+        let mut storage = app.contract_storage_mut(&agent_work_addr);
+        let user_stake: Map<(Addr, String), Uint128> = Map::new("user_stake");
+        let agent_stake: Map<(Addr, String), Uint128> = Map::new("agent_stake");
+        let job_owner: Map<String, Addr> = Map::new("job_owner");
+        let job_agent: Map<String, Vec<Addr>> = Map::new("job_agent");
+        for (job_id, amount) in [(last_key, 30), (past_last_key, 50)] {
+            user_stake.save(storage.as_mut(), (user2.clone(), job_id.to_string()), &Uint128::new(amount)).unwrap();
+            job_owner.save(storage.as_mut(), job_id.to_string(), &user2).unwrap();
+            agent_stake.save(storage.as_mut(), (agent1.clone(), job_id.to_string()), &Uint128::new(5)).unwrap();
+            job_agent.save(storage.as_mut(), job_id.to_string(), &vec![agent1.clone()]).unwrap();
+        }
+        drop(storage);
         app.execute_contract(
-            agent3.clone(),
-            agent_work_addr.clone(),
-            &ExecuteMsg::JurorVote { is_accept: false },
+            admin.clone(),
+            cw20_addr.clone(),
+            &Cw20ExecuteMsg::Transfer {
+                recipient: agent_work_addr.to_string(),
+                amount: Uint128::new(220),
+            },
             &[],
         )
         .unwrap();
+        let code_id = app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query).with_migrate(migrate)));
+        let migrate_msg = MigrateMsg {
+            baseline: Some(crate::msg::BaselineSettings {
+                admin: admin.clone(),
+                treasury: None,
+                protocol_fee_bps: 0,
+                dispute_config: None,
+            }),
+        };
+        app.migrate_contract(admin.clone(), agent_work_addr.clone(), &migrate_msg, code_id)
+            .unwrap();
 
-        let vote_result: VoteResultResponse = app
+        // the job at the largest key is migrated, the one past it is refunded
+        let job: JobResponse = app
             .wrap()
-            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetVoteResult {})
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id: last_key })
             .unwrap();
+        assert_eq!(job.owner, user2);
+        assert_eq!(job.budget, Uint128::new(30));
+        let res: StdResult<JobResponse> = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id: past_last_key });
+        assert!(res.is_err());
+        assert_eq!(query_claimable(&app, &agent_work_addr, user2.clone()), Uint128::new(50));
+        assert_eq!(query_claimable(&app, &agent_work_addr, agent1.clone()), Uint128::new(5));
 
-        assert_eq!(vote_result.accept_vote, Uint128::new(2));
-        assert_eq!(vote_result.reject_vote, Uint128::new(1));
+        // new jobs are numbered from the start, around the migrated ids
+        assert_eq!(create_job(&mut app, agent_work_addr.clone(), user1.clone()), Uint128::new(2));
+        assert_eq!(create_job(&mut app, agent_work_addr.clone(), user1.clone()), Uint128::new(3));
+        let job: JobResponse = app
+            .wrap()
+            .query_wasm_smart(&agent_work_addr, &QueryMsg::GetJob { job_id: last_key })
+            .unwrap();
+        assert_eq!(job.owner, user2);
     }
 }
@@ -1,22 +1,207 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use thiserror::Error;
 
+use crate::state::JobStatus;
+
 #[derive(Error, Debug)]
 pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    SemVer(#[from] semver::Error),
+
+    #[error("Cannot migrate from contract {contract}")]
+    CannotMigrate { contract: String },
+
+    #[error("Cannot migrate from version {stored} down to {current}")]
+    CannotDowngrade { stored: String, current: String },
+
+    #[error("Migrating from the unversioned layout needs the baseline settings")]
+    BaselineSettingsRequired {},
+
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("No admin transfer is pending")]
+    NoPendingAdmin {},
+
+    #[error("Admin transfer has expired")]
+    AdminTransferExpired {},
+
+    #[error("Contract is paused for {operation}")]
+    Paused { operation: String },
+
     #[error("Insufficient stake")]
     InsufficientStake {},
 
+    #[error("Agents keep at least {min_agent_stake} staked on the job, or unstake all of it")]
+    BelowMinAgentStake { min_agent_stake: Uint128 },
+
+    #[error("Only {available} can be withdrawn")]
+    InsufficientClaimable { available: Uint128 },
+
+    #[error("Expected exactly {amount}{denom} in funds")]
+    InvalidFunds { amount: Uint128, denom: String },
+
+    #[error("Native funds are not accepted for a cw20 payment")]
+    UnexpectedFunds {},
+
+    #[error("Asset {asset} is not accepted")]
+    AssetNotAccepted { asset: String },
+
+    #[error("Asset {asset} pays the juror and appeal bonds and can not be removed")]
+    BondAssetNotRemovable { asset: String },
+
+    #[error("Expected payment in {expected}")]
+    WrongAsset { expected: String },
+
     #[error("Already voted")]
     AlreadyVoted {},
 
     #[error("Not Job Owner")]
     NotJobOwner {},
+
+    #[error("Job {job_id} not found")]
+    JobNotFound { job_id: Uint128 },
+
+    #[error("Action not allowed while job {job_id} is {status:?}")]
+    InvalidJobStatus { job_id: Uint128, status: JobStatus },
+
+    #[error("Not Job Agent")]
+    NotJobAgent {},
+
+    #[error("Job has no agents")]
+    NoAgents {},
+
+    #[error("Application not found")]
+    ApplicationNotFound {},
+
+    #[error("Already applied to the job")]
+    AlreadyApplied {},
+
+    #[error("Application is not pending")]
+    ApplicationNotPending {},
+
+    #[error("Only agents hired by the job owner can stake on the job")]
+    NotHired {},
+
+    #[error("Job already hired its {max_agents} agents")]
+    MaxAgentsReached { max_agents: u32 },
+
+    #[error("Agent is still staked on the job")]
+    AgentOnJob {},
+
+    #[error("No auction for job {job_id}")]
+    AuctionNotFound { job_id: Uint128 },
+
+    #[error("Agents are hired through the running auction")]
+    AuctionInProgress {},
+
+    #[error("Invalid auction: {reason}")]
+    InvalidAuction { reason: String },
+
+    #[error("Bidding is closed")]
+    BiddingClosed {},
+
+    #[error("Already bid in the auction")]
+    AlreadyBid {},
+
+    #[error("The auction takes at most {max_bids} bids")]
+    TooManyBids { max_bids: u32 },
+
+    #[error("Bids can only be revealed during the reveal period")]
+    NotBidRevealPeriod {},
+
+    #[error("No committed bid to reveal")]
+    NoBidFound {},
+
+    #[error("Revealed bid does not match the commitment")]
+    InvalidBidReveal {},
+
+    #[error("Bid collateral must be non-zero, at least {min_agent_stake} and covered by the deposit of {deposit}")]
+    InvalidBidCollateral { min_agent_stake: Uint128, deposit: Uint128 },
+
+    #[error("Reveal period has not finished yet")]
+    AuctionNotFinished {},
+
+    #[error("Auction is already settled")]
+    AuctionSettled {},
+
+    #[error("Nothing to distribute")]
+    NothingToDistribute {},
+
+    #[error("Dispute for job {job_id} not found")]
+    DisputeNotFound { job_id: Uint128 },
+
+    #[error("Voting is closed")]
+    VotingClosed {},
+
+    #[error("Votes can only be revealed during the reveal period")]
+    NotRevealPeriod {},
+
+    #[error("No committed vote to reveal")]
+    NoCommitFound {},
+
+    #[error("Revealed vote does not match the commitment")]
+    InvalidReveal {},
+
+    #[error("Voting has not finished yet")]
+    VotingNotFinished {},
+
+    #[error("Dispute is already finalized")]
+    DisputeFinalized {},
+
+    #[error("Dispute cannot be appealed")]
+    NotAppealable {},
+
+    #[error("Only undecided disputes with up to {max_escrow} of {asset} at stake can be resolved by an arbiter")]
+    NotArbitrable { asset: String, max_escrow: Uint128 },
+
+    #[error("Appeal period has not finished yet")]
+    AppealPeriodNotFinished {},
+
+    #[error("Invalid config: {reason}")]
+    InvalidConfig { reason: String },
+
+    #[error("Invalid dispute config: {reason}")]
+    InvalidDisputeConfig { reason: String },
+
+    #[error("Not a bonded juror")]
+    NotJuror {},
+
+    #[error("Not on the panel drawn for this dispute")]
+    NotPanelMember {},
+
+    #[error("The other side already committed a seed")]
+    SeedAlreadyCommitted {},
+
+    #[error("Seeds are revealed once both sides committed, until the draw deadline")]
+    NotSeedRevealPeriod {},
+
+    #[error("Seed does not match the committed hash")]
+    InvalidSeed {},
+
+    #[error("Seed is already revealed")]
+    SeedAlreadyRevealed {},
+
+    #[error("Panel is already drawn")]
+    PanelAlreadyDrawn {},
+
+    #[error("No eligible jurors to draw a panel from")]
+    NoEligibleJurors {},
+
+    #[error("Juror bond must be at least {min_bond}")]
+    JurorBondTooLow { min_bond: Uint128 },
+
+    #[error("The juror registry is full with {max_jurors} jurors")]
+    JurorRegistryFull { max_jurors: u32 },
+
+    #[error("Unbonding period has not finished")]
+    UnbondingNotFinished {},
+
+    #[error("Nothing to withdraw")]
+    NothingToWithdraw {},
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
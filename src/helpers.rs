@@ -1,7 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
-use cosmwasm_std::{to_json_binary, Addr, CosmosMsg, StdResult, WasmMsg};
+use cosmwasm_std::{to_json_binary, Addr, Binary, CosmosMsg, StdResult, Uint128, WasmMsg};
 
 use crate::msg::ExecuteMsg;
 
@@ -25,3 +26,45 @@ impl CwTemplateContract {
         .into())
     }
 }
+
+/// Hash a juror commits to with `CommitVote` and opens with `RevealVote`:
+/// sha256("{job_id}:{round}:{juror}:{is_accept}:{salt}").
+/// The juror address is part of the preimage so a commitment cannot be copied by another juror,
+/// and the round so a salt used again on appeal does not give the vote away.
+pub fn vote_commitment(job_id: Uint128, round: u32, juror: &Addr, is_accept: bool, salt: &str) -> Binary {
+    let preimage = format!("{job_id}:{round}:{juror}:{is_accept}:{salt}");
+    Binary::from(Sha256::digest(preimage.as_bytes()).to_vec())
+}
+
+/// Hash an agent commits to with `CommitBid` and opens with `RevealBid`:
+/// sha256("{job_id}:{bidder}:{rate}:{collateral}:{salt}").
+pub fn bid_commitment(job_id: Uint128, bidder: &Addr, rate: Uint128, collateral: Uint128, salt: &str) -> Binary {
+    let preimage = format!("{job_id}:{bidder}:{rate}:{collateral}:{salt}");
+    Binary::from(Sha256::digest(preimage.as_bytes()).to_vec())
+}
+
+/// Hash each side of a dispute commits to with `OpenDispute` or `CommitSeed` and opens with `RevealSeed`: sha256(seed).
+pub fn seed_commitment(seed: &str) -> Binary {
+    Binary::from(Sha256::digest(seed.as_bytes()).to_vec())
+}
+
+/// Fold `salt` into `entropy`: sha256(entropy || salt).
+pub fn mix_entropy(entropy: &Binary, salt: &str) -> Binary {
+    let mut hasher = Sha256::new();
+    hasher.update(entropy.as_slice());
+    hasher.update(salt.as_bytes());
+    Binary::from(hasher.finalize().to_vec())
+}
+
+/// Random number for the `draw`-th pick of a dispute panel.
+/// Nothing about the block goes in, the party drawing the panel chooses it.
+pub fn panel_randomness(seed: &[u8], job_id: Uint128, draw: u32) -> u128 {
+    let mut hasher = Sha256::new();
+    hasher.update(seed);
+    hasher.update(job_id.to_be_bytes());
+    hasher.update(draw.to_be_bytes());
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    u128::from_be_bytes(bytes)
+}
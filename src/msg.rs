@@ -1,53 +1,291 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Binary, Timestamp, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw_utils::Expiration;
+
+use crate::state::{ApplicationStatus, Asset, AssetAmount, Auction, Config, DisputeConfig, DisputeOutcome, DisputeRound, JobStatus, Ownership, PanelDraw, PauseFlags, Role, TokenInfo};
 
 #[cw_serde]
 pub struct InstantiateMsg {
-    pub token_symbol: String,
-    pub token_contract_addr: Addr,
+    /// Assets jobs can be paid in and agents can stake, the sender becomes the admin managing them
+    pub accepted_assets: Vec<TokenInfo>,
+    /// Asset juror bonds and appeal bonds are paid in, must be one of `accepted_assets`
+    pub bond_asset: Asset,
+    /// Falls back to `DisputeConfig::default()` when omitted
+    pub dispute_config: Option<DisputeConfig>,
+    /// Falls back to the sender when omitted
+    pub treasury: Option<Addr>,
+    pub protocol_fee_bps: u16,
+    /// Granted `Role::Guardian` when set
+    pub guardian: Option<Addr>,
+    /// Limits of `ARBITER_MAX_ESCROW`, every asset must be one of `accepted_assets`
+    pub arbiter_max_escrow: Vec<AssetAmount>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Only read when migrating the unversioned first deployment, which stored no admin
+    pub baseline: Option<BaselineSettings>,
+}
+
+/// Settings the first deployment did not have, its cw20 token becomes the only accepted asset
+#[cw_serde]
+pub struct BaselineSettings {
+    pub admin: Addr,
+    /// Falls back to `admin` when omitted
+    pub treasury: Option<Addr>,
+    pub protocol_fee_bps: u16,
+    /// Falls back to `DisputeConfig::default()` when omitted
+    pub dispute_config: Option<DisputeConfig>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
+    /// Hook for `Cw20ExecuteMsg::Send` from any whitelisted cw20 asset, carrying a `ReceiveMsg`
+    Receive(Cw20ReceiveMsg),
+    CreateJob {
+        title: String,
+        spec_uri: String,
+        spec_hash: Option<String>,
+        budget: Uint128,
+        /// Must be an accepted asset
+        payment_asset: Asset,
+        /// See `Job::min_agent_stake`
+        min_agent_stake: Uint128,
+        /// See `Job::max_agents`
+        max_agents: Option<u32>,
+    },
+    StartJob {
+        job_id: Uint128,
+    },
+    SubmitWork {
+        job_id: Uint128,
+    },
+    CompleteJob {
+        job_id: Uint128,
+    },
+    /// `seed_hash` is `helpers::seed_commitment` of the opener's seed, see `Dispute::draw`
+    OpenDispute {
+        job_id: Uint128,
+        reason: String,
+        seed_hash: Binary,
+    },
+    /// The other side of the dispute commits to its seed: the owner when an agent opened it,
+    /// any agent of the job otherwise
+    CommitSeed {
+        job_id: Uint128,
+        seed_hash: Binary,
+    },
+    /// Once both seeds are committed, each side reveals its own; the second reveal draws the juror panel
+    RevealSeed {
+        job_id: Uint128,
+        seed: String,
+    },
+    CancelJob {
+        job_id: Uint128,
+    },
+    SetPayoutAuthority {
+        job_id: Uint128,
+        authority: Option<Addr>,
+    },
     UserStake {
         amount: Uint128,
         job_id: Uint128,
     },
+    /// Owner only, while the job is open or cancelled, or completed with no agent left on it
     UserUnstake {
         amount: Uint128,
         job_id: Uint128,
     },
+    /// Sender must be hired for the job, it is paid the rate it proposed
     AgentStake {
         amount: Uint128,
         job_id: Uint128,
-        cost_per_unit_time: Uint128,
+        /// Accepted asset the collateral is paid in, the job's payment asset when omitted
+        collateral_asset: Option<Asset>,
     },
     AgentUnstake {
         amount: Uint128,
         job_id: Uint128,
     },
+    /// Applying again is only possible once rejected
+    ApplyToJob {
+        job_id: Uint128,
+        proposed_rate: Uint128,
+        proposal_uri: String,
+    },
+    /// Job owner only, hires the agent if the job has room for it
+    AcceptApplication {
+        job_id: Uint128,
+        agent: Addr,
+    },
+    /// Job owner only, for pending applications, or accepted ones whose agent has not staked yet
+    RejectApplication {
+        job_id: Uint128,
+        agent: Addr,
+    },
+    /// Hired agents must have unstaked everything first
+    WithdrawApplication {
+        job_id: Uint128,
+    },
+    /// Job owner only, hires up to 20 `slots` agents through a sealed-bid auction instead of applications.
+    /// Bids are committed for `commit_period` seconds then revealed for `reveal_period` seconds,
+    /// the auction takes 10 bids per slot
+    OpenAuction {
+        job_id: Uint128,
+        slots: u32,
+        commit_period: u64,
+        reveal_period: u64,
+    },
+    /// `hash` is `helpers::bid_commitment`, `deposit` is paid in the job's payment asset,
+    /// must cover the collateral of the bid and be at least the job's `min_agent_stake`
+    CommitBid {
+        job_id: Uint128,
+        hash: Binary,
+        deposit: Uint128,
+    },
+    /// Open a committed bid during the reveal period, bids never revealed lose the auction and their deposit
+    RevealBid {
+        job_id: Uint128,
+        rate: Uint128,
+        collateral: Uint128,
+        salt: String,
+    },
+    /// Anyone once the reveal period is over. The lowest revealed rates are hired and staked at
+    /// their collateral, the rest of the winning deposits and the losing ones are credited back,
+    /// unrevealed deposits are credited to the job owner
+    SettleAuction {
+        job_id: Uint128,
+    },
     DistributeRewardsByAgent {
         job_id: Uint128
     },
     DistributeRewardsByTime {
         job_id: Uint128
     },
-    JurorVote {
+    /// Commit to a vote during the commit period, `hash` is `helpers::vote_commitment`
+    CommitVote {
+        job_id: Uint128,
+        hash: Binary,
+    },
+    /// Open a committed vote during the reveal period
+    RevealVote {
+        job_id: Uint128,
+        is_accept: bool,
+        salt: String,
+    },
+    /// Tally the round once the reveal period is over, and release the escrow once the
+    /// outcome can no longer be appealed. Anyone can call it
+    FinalizeDispute {
+        job_id: Uint128,
+    },
+    /// Losing side of the last round pays `DisputeConfig::appeal_bond_for` to have a larger panel vote again.
+    /// The panel is drawn as for `OpenDispute`, `seed_hash` is the appellant's commitment
+    Appeal {
+        job_id: Uint128,
+        seed_hash: Binary,
+    },
+    RegisterJuror {
+        amount: Uint128,
+    },
+    UnregisterJuror {},
+    WithdrawJurorBond {},
+    /// Pay out part of the balance credited to the sender in `asset`
+    Withdraw {
+        asset: Asset,
+        amount: Uint128,
+    },
+    /// Omitted fields are left unchanged. `treasury` and `protocol_fee_bps` need `Role::FeeManager`,
+    /// `arbiter_max_escrow` needs `Role::Admin` and sets the limit of a single asset, 0 removes it
+    UpdateConfig {
+        treasury: Option<Addr>,
+        protocol_fee_bps: Option<u16>,
+        arbiter_max_escrow: Option<AssetAmount>,
+    },
+    /// Admin only, replaces any pending proposal. `addr` becomes the admin once it accepts before `expiry`
+    ProposeNewAdmin {
+        addr: Addr,
+        expiry: Option<Expiration>,
+    },
+    /// Pending admin only
+    AcceptAdmin {},
+    /// Admin only, leaves the contract without admin and drops a pending proposal.
+    /// Holders of `Role::Admin` keep their role
+    RenounceAdmin {},
+    /// `Role::JurorManager` only, applies to disputes opened afterwards and to running ones from their next step
+    UpdateDisputeConfig {
+        dispute_config: DisputeConfig,
+    },
+    /// `Role::Admin` only
+    GrantRole {
+        role: Role,
+        addr: Addr,
+    },
+    /// `Role::Admin` only
+    RevokeRole {
+        role: Role,
+        addr: Addr,
+    },
+    /// `Role::Arbiter` only, settles a dispute no round was tallied for as if jurors decided `is_accept`
+    ResolveDispute {
+        job_id: Uint128,
         is_accept: bool,
     },
-    ResetVote {},
+    /// `Role::Guardian` only, freezes the flagged operations on top of the ones already paused
+    Pause {
+        flags: PauseFlags,
+    },
+    /// `Role::Guardian` only, lifts the flagged operations and leaves the others paused, lifting
+    /// voting pushes the deadlines of open disputes back by the time it was paused
+    Unpause {
+        flags: PauseFlags,
+    },
+    /// `Role::Admin` only
+    AddAsset {
+        token_denom: String,
+        asset: Asset,
+    },
+    /// `Role::Admin` only, jobs and stakes already in the asset are not affected, the bond asset stays
+    RemoveAsset {
+        asset: Asset,
+    },
+}
+
+/// Sent as the `msg` of `Cw20ExecuteMsg::Send`, the sent amount is what gets staked or bonded
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Same as `UserStake`
+    FundJob {
+        job_id: Uint128,
+    },
+    /// Same as `AgentStake`, the collateral is the sent token
+    AgentStake {
+        job_id: Uint128,
+    },
+    /// Same as `RegisterJuror`
+    BondJuror {},
+    /// Same as `CommitBid`, the deposit is the sent token
+    CommitBid {
+        job_id: Uint128,
+        hash: Binary,
+    },
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    #[returns(Uint128)]
+    #[returns(JobResponse)]
+    GetJob {
+        job_id: Uint128
+    },
+
+    #[returns(AssetAmount)]
     GetUserStake { 
         user_addr: Addr,
         job_id: Uint128
     },
 
-    #[returns(Uint128)]
+    #[returns(AssetAmount)]
     GetAgentStake { 
         agent_addr: Addr,
         job_id: Uint128
@@ -58,8 +296,71 @@ pub enum QueryMsg {
         job_id: Uint128
     },
 
-    #[returns(TokenInfoResponse)]
-    GetTokenInfo {},
+    /// Cost per unit of time of each agent of the job, fixed once the owner accepts its application
+    #[returns(Vec<AgentRateResponse>)]
+    GetAgentRates {
+        job_id: Uint128
+    },
+
+    #[returns(ApplicationResponse)]
+    GetApplication {
+        job_id: Uint128,
+        agent: Addr,
+    },
+
+    #[returns(ListApplicationsResponse)]
+    ListApplications {
+        job_id: Uint128,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+
+    #[returns(Auction)]
+    GetAuction {
+        job_id: Uint128,
+    },
+
+    #[returns(ListBidsResponse)]
+    ListBids {
+        job_id: Uint128,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+
+    #[returns(AssetsResponse)]
+    GetAssets {},
+
+    #[returns(Config)]
+    GetConfig {},
+
+    /// Limits of `ResolveDispute`, one per asset that has one
+    #[returns(Vec<AssetAmount>)]
+    GetArbiterMaxEscrow {},
+
+    #[returns(PauseFlags)]
+    GetPauseFlags {},
+
+    #[returns(Ownership)]
+    GetOwnership {},
+
+    /// Also true for the admin and for holders of `Role::Admin`
+    #[returns(bool)]
+    HasRole {
+        role: Role,
+        addr: Addr,
+    },
+
+    /// Addresses granted the role, without the admin
+    #[returns(RoleMembersResponse)]
+    ListRoleMembers {
+        role: Role,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+
+    /// Protocol fees collected per asset
+    #[returns(Vec<AssetAmount>)]
+    GetProtocolFees {},
 
     #[returns(bool)]
     CheckIfEnoughRewards {
@@ -67,17 +368,166 @@ pub enum QueryMsg {
     },
 
     #[returns(VoteResultResponse)]
-    GetVoteResult {},
+    GetVoteResult {
+        job_id: Uint128
+    },
+
+    #[returns(DisputeResponse)]
+    GetDispute {
+        job_id: Uint128
+    },
+
+    #[returns(ListDisputesResponse)]
+    ListDisputes {
+        start_after: Option<Uint128>,
+        limit: Option<u32>,
+    },
+
+    #[returns(JurorResponse)]
+    GetJuror {
+        juror_addr: Addr,
+    },
+
+    #[returns(ListJurorsResponse)]
+    ListJurors {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+
+    /// Balances credited by payouts and juror rewards, waiting for a `Withdraw`
+    #[returns(Vec<AssetAmount>)]
+    GetClaimable {
+        addr: Addr,
+    },
+}
+
+#[cw_serde]
+pub struct JobResponse {
+    pub id: Uint128,
+    pub owner: Addr,
+    pub title: String,
+    pub spec_uri: String,
+    pub spec_hash: Option<String>,
+    pub budget: Uint128,
+    pub payment_asset: Asset,
+    pub created_at: Timestamp,
+    pub status: JobStatus,
+    pub payout_authority: Option<Addr>,
+    pub min_agent_stake: Uint128,
+    pub max_agents: Option<u32>,
+}
+
+#[cw_serde]
+pub struct AgentRateResponse {
+    pub agent: Addr,
+    pub cost_per_unit_time: Uint128,
+}
+
+#[cw_serde]
+pub struct ApplicationResponse {
+    pub agent: Addr,
+    pub proposed_rate: Uint128,
+    pub proposal_uri: String,
+    pub applied_at: Timestamp,
+    pub status: ApplicationStatus,
+}
+
+#[cw_serde]
+pub struct ListApplicationsResponse {
+    pub applications: Vec<ApplicationResponse>,
+}
+
+#[cw_serde]
+pub struct BidResponse {
+    pub bidder: Addr,
+    pub commitment: Binary,
+    pub deposit: Uint128,
+    pub committed_at: Timestamp,
+    pub rate: Option<Uint128>,
+    pub collateral: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct ListBidsResponse {
+    pub bids: Vec<BidResponse>,
+}
+
+#[cw_serde]
+pub struct DisputeResponse {
+    pub job_id: Uint128,
+    pub opener: Addr,
+    pub reason: String,
+    pub draw: Option<PanelDraw>,
+    pub round: u32,
+    pub panel: Vec<Addr>,
+    pub commits: Uint128,
+    pub accept_votes: Uint128,
+    pub reject_votes: Uint128,
+    pub accept_weight: Uint128,
+    pub reject_weight: Uint128,
+    pub opened_at: Timestamp,
+    pub commit_deadline: Timestamp,
+    pub reveal_deadline: Timestamp,
+    pub rounds: Vec<DisputeRound>,
+    pub appeal_deadline: Option<Timestamp>,
+    pub outcome: Option<DisputeOutcome>,
+}
+
+#[cw_serde]
+pub struct ListDisputesResponse {
+    pub disputes: Vec<DisputeResponse>,
+}
+
+#[cw_serde]
+pub struct RoleMembersResponse {
+    pub members: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct JurorResponse {
+    pub address: Addr,
+    pub bonded: Uint128,
+    pub unbonding: Uint128,
+    pub unbonding_release_at: Option<Timestamp>,
+    pub registered_at: Timestamp,
+}
+
+#[cw_serde]
+pub struct ListJurorsResponse {
+    pub jurors: Vec<JurorResponse>,
 }
 
 #[cw_serde]
 pub struct VoteResultResponse {
+    /// Number of jurors on each side
     pub accept_vote: Uint128,
     pub reject_vote: Uint128,
+    /// Stake weighted totals on each side
+    pub accept_weight: Uint128,
+    pub reject_weight: Uint128,
+}
+
+/// Query interface expected from `DisputeConfig::randomness_beacon`
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum BeaconQueryMsg {
+    #[returns(RandomnessResponse)]
+    Randomness {},
+}
+
+#[cw_serde]
+pub struct RandomnessResponse {
+    pub randomness: Binary,
 }
 
 #[cw_serde]
 pub struct TokenInfoResponse {
     pub token_denom: String,
-    pub token_address: Addr,
+    pub asset: Asset,
+}
+
+#[cw_serde]
+pub struct AssetsResponse {
+    pub accepted_assets: Vec<TokenInfoResponse>,
+    pub bond_asset: Asset,
 }
@@ -1,24 +1,487 @@
+use std::fmt;
+
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Binary, Empty, Isqrt, StdError, StdResult, Timestamp, Uint128};
 use cw_storage_plus::{Item, Map};
+use cw_utils::Expiration;
+
+#[cw_serde]
+pub enum Asset {
+    Cw20 { addr: Addr },
+    /// Bank denom, including Injective tokenfactory denoms (`factory/{creator}/{subdenom}`)
+    Native { denom: String },
+}
+
+/// "cw20:{addr}" or "native:{denom}", also used as the storage key of an asset
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Asset::Cw20 { addr } => write!(f, "cw20:{addr}"),
+            Asset::Native { denom } => write!(f, "native:{denom}"),
+        }
+    }
+}
+
+#[cw_serde]
+pub struct AssetAmount {
+    pub asset: Asset,
+    pub amount: Uint128,
+}
 
 #[cw_serde]
 pub struct TokenInfo {
+    /// Display symbol of the asset
     pub token_denom: String,
-    pub token_address: Addr,
+    pub asset: Asset,
+}
+
+#[cw_serde]
+pub struct Config {
+    /// Receives the protocol fee as a claimable balance
+    pub treasury: Addr,
+    /// Share of the rewards released to agents kept as protocol fee
+    pub protocol_fee_bps: u16,
+}
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// asset -> largest amount in that asset, escrow and agent collateral together, an arbiter may
+/// settle a dispute over, `ResolveDispute` is disabled for jobs with stakes in an asset without a limit
+pub const ARBITER_MAX_ESCROW: Map<String, AssetAmount> = Map::new("arbiter_max_escrow");
+
+/// Admin of the contract, handed over in two steps so a wrong address can not take over
+#[cw_serde]
+#[derive(Default)]
+pub struct Ownership {
+    /// Holds every role, none once renounced
+    pub owner: Option<Addr>,
+    /// Proposed by `ProposeNewAdmin`, becomes the owner with `AcceptAdmin`
+    pub pending_owner: Option<Addr>,
+    /// The pending owner can no longer accept after this
+    pub pending_expiry: Option<Expiration>,
+}
+pub const OWNERSHIP: Item<Ownership> = Item::new("ownership");
+
+#[cw_serde]
+pub enum Role {
+    /// Grants and revokes roles, manages the accepted assets, implies every other role
+    Admin,
+    /// Pauses and unpauses
+    Guardian,
+    /// Settles disputes with up to `ARBITER_MAX_ESCROW` at stake without a juror vote
+    Arbiter,
+    /// Sets the treasury and the protocol fee
+    FeeManager,
+    /// Sets the dispute config the juror registry and panels follow
+    JurorManager,
+}
+
+/// Same as the JSON name, also used as the storage key of a role
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Role::Admin => "admin",
+            Role::Guardian => "guardian",
+            Role::Arbiter => "arbiter",
+            Role::FeeManager => "fee_manager",
+            Role::JurorManager => "juror_manager",
+        };
+        f.write_str(name)
+    }
+}
+
+/// (role, member) -> granted, keyed by `Role::to_string`. `Ownership::owner` holds every role without an entry
+pub const ROLES: Map<(String, Addr), Empty> = Map::new("roles");
+
+/// Operations frozen by `Pause`, each flag is lifted on its own by `Unpause`
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseFlags {
+    /// Job funding, agent collateral and juror bonds
+    pub staking: bool,
+    /// Unstaking and releasing or refunding escrow, including dispute settlement
+    pub payouts: bool,
+    /// Opening, voting on and appealing disputes
+    pub voting: bool,
+    /// `Withdraw`, juror rewards and unbonded juror stake, all balances that are already credited
+    pub withdrawals: bool,
+}
+pub const PAUSED: Item<PauseFlags> = Item::new("paused");
+
+/// When voting was paused, `Unpause` pushes the deadlines of open disputes back by the paused time
+pub const VOTING_PAUSED_AT: Item<Timestamp> = Item::new("voting_paused_at");
+
+/// Protocol fees collected since instantiation, keyed by `Asset::to_string`
+pub const PROTOCOL_FEES: Map<String, AssetAmount> = Map::new("protocol_fees");
+
+/// Assets jobs can be paid in and agents can stake, keyed by `Asset::to_string`
+pub const ACCEPTED_ASSETS: Map<String, TokenInfo> = Map::new("accepted_assets");
+
+/// Asset juror bonds and appeal bonds are paid in
+pub const BOND_ASSET: Item<Asset> = Item::new("bond_asset");
+
+/// Open -> InProgress -> Submitted -> Completed
+///                              \-> Disputed
+/// Open -> Cancelled
+#[cw_serde]
+pub enum JobStatus {
+    /// Owner funds the job, hires agents from their applications and hired agents stake to join it
+    Open,
+    /// Owner started the job, the agent set is fixed
+    InProgress,
+    /// An agent submitted the work, waiting for the owner to accept or dispute it
+    Submitted,
+    /// Owner accepted the work, rewards can be distributed
+    Completed,
+    /// Submitted work is contested
+    Disputed,
+    /// Owner cancelled the job before it started, stakes can be withdrawn
+    Cancelled,
+}
+
+#[cw_serde]
+pub struct Job {
+    pub id: Uint128,
+    pub owner: Addr,
+    pub title: String,
+    /// Where the full job specification lives (IPFS, https, ...)
+    pub spec_uri: String,
+    /// Optional hash of the specification document so agents can verify it
+    pub spec_hash: Option<String>,
+    pub budget: Uint128,
+    /// Asset the owner funds the job in and agents are paid in
+    pub payment_asset: Asset,
+    pub created_at: Timestamp,
+    pub status: JobStatus,
+    /// Arbiter approved by the owner to release payouts on their behalf
+    pub payout_authority: Option<Addr>,
+    /// Collateral an agent must keep staked to be on the job, an agent with no stake is never on it
+    pub min_agent_stake: Uint128,
+    /// Most applications the owner can accept, no limit when unset
+    pub max_agents: Option<u32>,
+}
+
+/// Last job id handed out by `CreateJob`, ids start at 1
+pub const JOB_COUNT: Item<Uint128> = Item::new("job_count");
+pub const JOBS: Map<u64, Job> = Map::new("jobs");
+
+/// Storage key of a job, job maps are keyed by `u64` so ids sort numerically.
+/// No job has a larger id, `CreateJob` fails rather than hand one out
+pub fn job_key(job_id: Uint128) -> StdResult<u64> {
+    u64::try_from(job_id.u128()).map_err(|_| StdError::generic_err(format!("job id {job_id} is out of range")))
+}
+
+/// Escrow of the job owner, always in the job's payment asset
+pub const USER_STAKE: Map<(Addr, u64), AssetAmount> = Map::new("user_stake");
+
+/// Agent collateral, in any accepted asset picked by the agent on the first stake
+pub const AGENT_STAKE: Map<(Addr, u64), AssetAmount> = Map::new("agent_stake");
+
+#[cw_serde]
+pub struct AgentSlot {
+    /// The `proposed_rate` of the application, fixed once the owner accepts it
+    pub cost_per_unit_time: Uint128,
+}
+
+/// (job id, agent) -> slot of an agent on the job, taken by the stake reaching
+/// `Job::min_agent_stake` and released by unstaking all of it or by the payout or refund of the job
+pub const JOB_AGENTS: Map<(u64, Addr), AgentSlot> = Map::new("job_agents");
+
+#[cw_serde]
+pub enum ApplicationStatus {
+    /// Waiting for the job owner
+    Pending,
+    /// Hired, the agent joins the job by staking at the proposed rate
+    Accepted,
+    /// The agent can apply again
+    Rejected,
+}
+
+#[cw_serde]
+pub struct Application {
+    pub proposed_rate: Uint128,
+    /// Where the proposal of the agent lives (IPFS, https, ...)
+    pub proposal_uri: String,
+    pub applied_at: Timestamp,
+    pub status: ApplicationStatus,
 }
-pub const TOKEN_INFO: Item<TokenInfo> = Item::new("token_info");
 
-pub const USER_STAKE: Map<(Addr, String), Uint128> = Map::new("user_stake");
+/// (job id, agent) -> application of the agent, removed when the agent withdraws it
+pub const APPLICATIONS: Map<(u64, Addr), Application> = Map::new("applications");
+
+/// Number of accepted applications of a job, bounded by `Job::max_agents`, dropped once the job pays out
+pub const JOB_HIRES: Map<u64, u32> = Map::new("job_hires");
 
-pub const AGENT_STAKE: Map<(Addr, String), Uint128> = Map::new("agent_stake");
+/// Sealed-bid reverse auction hiring agents for a job, bids are committed then revealed
+#[cw_serde]
+pub struct Auction {
+    /// Number of agents hired, the lowest rates win
+    pub slots: u32,
+    pub commit_deadline: Timestamp,
+    pub reveal_deadline: Timestamp,
+    /// Bids committed so far, up to 10 per slot
+    pub bids: u32,
+    /// Set by `SettleAuction`, the job hires through applications again
+    pub settled: bool,
+}
 
-pub const JOB_OWNER: Map<String, Addr> = Map::new("job_owner");
+/// Last auction of each job
+pub const AUCTIONS: Map<u64, Auction> = Map::new("auctions");
+
+#[cw_serde]
+pub struct Bid {
+    /// `helpers::bid_commitment`
+    pub commitment: Binary,
+    /// Paid in the job's payment asset with the commitment, hides the collateral and must cover it
+    pub deposit: Uint128,
+    pub committed_at: Timestamp,
+    /// Set once revealed
+    pub rate: Option<Uint128>,
+    pub collateral: Option<Uint128>,
+}
 
-pub const JOB_AGENT: Map<String, Vec<Addr>> = Map::new("job_agent");
+/// (job id, bidder) -> bid on the running auction of the job, removed when it is settled
+pub const BIDS: Map<(u64, Addr), Bid> = Map::new("bids");
+
+#[cw_serde]
+pub enum VoteWeighting {
+    /// Weight equals the bonded amount
+    Linear,
+    /// Weight is the integer square root of the bonded amount, dampening large jurors
+    SquareRoot,
+}
+
+#[cw_serde]
+pub struct DisputeConfig {
+    /// Seconds jurors have to commit their hashed vote once a dispute is opened
+    pub commit_period: u64,
+    /// Seconds jurors have to reveal their vote once the commit period ends
+    pub reveal_period: u64,
+    /// Minimum bond for a juror to be allowed to vote
+    pub min_juror_bond: Uint128,
+    /// Seconds an unregistered juror waits before the bond can be withdrawn
+    pub juror_unbonding_period: u64,
+    pub vote_weighting: VoteWeighting,
+    /// Upper bound applied to every vote weight, after `vote_weighting`
+    pub max_vote_weight: Option<Uint128>,
+    /// Minimum number of revealed votes for a dispute to be decided
+    pub quorum: u32,
+    /// Share of the agents' stake, in basis points, paid to the job owner when a dispute is rejected
+    pub agent_slash_bps: u16,
+    /// Share of the losing side's funds, in basis points, paid to the coherent jurors
+    pub dispute_fee_bps: u16,
+    /// Share of the bond, in basis points, slashed from jurors who voted with the minority or did not reveal
+    pub juror_slash_bps: u16,
+    /// Number of jurors drawn to vote on a dispute, all eligible jurors when there are fewer
+    pub panel_size: u32,
+    /// Most jurors registered at once, unbonding ones included, every panel draw goes through all of them
+    pub max_jurors: u32,
+    /// Contract answering `BeaconQueryMsg::Randomness`, mixed with the seeds of both sides to draw panels
+    pub randomness_beacon: Option<Addr>,
+    /// Seconds the losing side has to appeal a round before its outcome can be settled
+    pub appeal_period: u64,
+    /// Number of appeal rounds after the first vote
+    pub max_appeals: u32,
+    /// Bond to appeal the first round, doubled for every following appeal
+    pub appeal_bond: Uint128,
+}
 
-pub const AGENT_COST: Map<Addr, Uint128> = Map::new("agent_cost");
+impl DisputeConfig {
+    pub fn vote_weight(&self, bonded: Uint128) -> Uint128 {
+        let weight = match self.vote_weighting {
+            VoteWeighting::Linear => bonded,
+            VoteWeighting::SquareRoot => bonded.isqrt(),
+        };
+        match self.max_vote_weight {
+            Some(max_vote_weight) => weight.min(max_vote_weight),
+            None => weight,
+        }
+    }
+
+    /// Panel drawn for `round`, doubling with every appeal
+    pub fn panel_size_for(&self, round: u32) -> u32 {
+        self.panel_size.saturating_mul(2u32.saturating_pow(round))
+    }
+
+    /// Bond to appeal the outcome of `round`, doubling with every appeal
+    pub fn appeal_bond_for(&self, round: u32) -> Uint128 {
+        self.appeal_bond * Uint128::from(2u128.saturating_pow(round))
+    }
+}
+
+impl Default for DisputeConfig {
+    fn default() -> Self {
+        DisputeConfig {
+            commit_period: 2 * 24 * 60 * 60,
+            reveal_period: 24 * 60 * 60,
+            min_juror_bond: Uint128::new(100),
+            juror_unbonding_period: 7 * 24 * 60 * 60,
+            vote_weighting: VoteWeighting::Linear,
+            max_vote_weight: None,
+            quorum: 3,
+            agent_slash_bps: 1_000,
+            dispute_fee_bps: 500,
+            juror_slash_bps: 1_000,
+            panel_size: 5,
+            max_jurors: 100,
+            randomness_beacon: None,
+            appeal_period: 2 * 24 * 60 * 60,
+            max_appeals: 2,
+            appeal_bond: Uint128::new(100),
+        }
+    }
+}
+pub const DISPUTE_CONFIG: Item<DisputeConfig> = Item::new("dispute_config");
+
+#[cw_serde]
+pub enum DisputeOutcome {
+    /// Jurors upheld the submitted work
+    Accepted,
+    /// Jurors sided with the job owner
+    Rejected,
+    /// Same weight on both sides, every stake is refunded
+    Tied,
+    /// Fewer revealed votes than the quorum, every stake is refunded
+    NoQuorum,
+}
+
+impl fmt::Display for DisputeOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DisputeOutcome::Accepted => write!(f, "accepted"),
+            DisputeOutcome::Rejected => write!(f, "rejected"),
+            DisputeOutcome::Tied => write!(f, "tied"),
+            DisputeOutcome::NoQuorum => write!(f, "no_quorum"),
+        }
+    }
+}
+
+/// Result of a tallied round of votes
+#[cw_serde]
+pub struct DisputeRound {
+    pub panel: Vec<Addr>,
+    pub accept_votes: Uint128,
+    pub reject_votes: Uint128,
+    pub accept_weight: Uint128,
+    pub reject_weight: Uint128,
+    pub outcome: DisputeOutcome,
+    /// Losing party who appealed this outcome, and the bond they paid
+    pub appellant: Option<Addr>,
+    pub appeal_bond: Uint128,
+}
+
+impl DisputeRound {
+    /// Round decided without jurors, by an arbiter or against a side that missed its step of the draw
+    pub fn without_panel(outcome: DisputeOutcome) -> Self {
+        DisputeRound {
+            panel: vec![],
+            accept_votes: Uint128::zero(),
+            reject_votes: Uint128::zero(),
+            accept_weight: Uint128::zero(),
+            reject_weight: Uint128::zero(),
+            outcome,
+            appellant: None,
+            appeal_bond: Uint128::zero(),
+        }
+    }
+}
+
+/// Both sides of the dispute commit to a seed before either is revealed and the panel is
+/// drawn from the two, so neither side can pick the jurors
+#[cw_serde]
+pub struct PanelDraw {
+    /// Party who committed first, the opener of the dispute or the appellant
+    pub first_party: Addr,
+    /// `helpers::seed_commitment` of the first party's seed
+    pub first_hash: Binary,
+    /// `helpers::seed_commitment` of the other side's seed, committed with `CommitSeed`
+    pub second_hash: Option<Binary>,
+    /// Seeds opened with `RevealSeed`
+    pub first_seed: Option<String>,
+    pub second_seed: Option<String>,
+    /// Beacon randomness read when the second seed is committed, mixed with both seeds
+    pub beacon: Option<Binary>,
+    /// The other side commits its seed until this time, then both sides reveal until the next one.
+    /// A side that misses its step loses the dispute
+    pub deadline: Timestamp,
+}
+
+#[cw_serde]
+pub struct Dispute {
+    pub job_id: Uint128,
+    pub opener: Addr,
+    pub reason: String,
+    /// Seeds of the panel being drawn, cleared once the panel is drawn
+    pub draw: Option<PanelDraw>,
+    /// Round being voted on, 0 for the first vote and one more per appeal.
+    /// The panel, tallies and deadlines below belong to this round
+    pub round: u32,
+    /// Jurors drawn to vote, empty until the panel is drawn
+    pub panel: Vec<Addr>,
+    /// Number of jurors who committed a vote
+    pub commits: Uint128,
+    /// Head count of revealed votes on each side
+    pub accept_votes: Uint128,
+    pub reject_votes: Uint128,
+    /// Sum of the revealed vote weights on each side
+    pub accept_weight: Uint128,
+    pub reject_weight: Uint128,
+    pub opened_at: Timestamp,
+    /// Hashed votes are accepted until this time, restarted when the panel is drawn
+    pub commit_deadline: Timestamp,
+    /// Committed votes can be revealed until this time, unrevealed ones are forfeited
+    pub reveal_deadline: Timestamp,
+    /// Tallied rounds, the last one is the outcome that stands unless appealed
+    pub rounds: Vec<DisputeRound>,
+    /// Set once the current round is tallied, the losing side may appeal until then
+    pub appeal_deadline: Option<Timestamp>,
+    /// Final outcome, set once the escrow is released
+    pub outcome: Option<DisputeOutcome>,
+}
+
+#[cw_serde]
+pub struct Juror {
+    /// Stake backing the juror's votes
+    pub bonded: Uint128,
+    /// Stake released by `UnregisterJuror`, withdrawable after `unbonding_release_at`
+    pub unbonding: Uint128,
+    pub unbonding_release_at: Option<Timestamp>,
+    pub registered_at: Timestamp,
+}
+
+pub const JURORS: Map<Addr, Juror> = Map::new("jurors");
+
+/// Number of entries in `JURORS`, bounded by `DisputeConfig::max_jurors`
+pub const JUROR_COUNT: Item<u32> = Item::new("juror_count");
+
+/// Bonds slashed in rounds without a coherent juror to reward, in the bond asset, added to the
+/// rewards of the next round that has one
+pub const JUROR_POOL: Item<Uint128> = Item::new("juror_pool");
+
+/// (account, asset) -> payouts, refunds and juror rewards credited by distributions and dispute settlements,
+/// paid out by `Withdraw` so a payout never depends on a transfer to every recipient
+pub const CLAIMABLE: Map<(Addr, String), AssetAmount> = Map::new("claimable");
+
+/// One dispute per job, keyed like the job maps
+pub const DISPUTES: Map<u64, Dispute> = Map::new("disputes");
+
+/// Disputes without an outcome yet, the only ones whose deadlines `Unpause` has to push back
+pub const OPEN_DISPUTES: Map<u64, Empty> = Map::new("open_disputes");
+#[cw_serde]
+pub struct VoteCommit {
+    /// See `helpers::vote_commitment`
+    pub commitment: Binary,
+    /// Weight at the time of commit, later bond changes do not affect it
+    pub weight: Uint128,
+}
+
+/// (job id, round, juror) -> commit not revealed yet, removed once revealed
+pub const VOTE_COMMITS: Map<(u64, u32, Addr), VoteCommit> = Map::new("vote_commits");
+
+#[cw_serde]
+pub struct Vote {
+    pub is_accept: bool,
+    pub weight: Uint128,
+}
 
-pub const ACCEPT_VOTE: Item<Uint128> = Item::new("accpect_vote");
-pub const REJECT_VOTE: Item<Uint128> = Item::new("reject_vote");
-pub const IS_JUROR_VOTED: Map<Addr, bool> = Map::new("is_juror_voted");
+/// (job id, round, juror) -> revealed vote
+pub const DISPUTE_VOTES: Map<(u64, u32, Addr), Vote> = Map::new("dispute_votes");